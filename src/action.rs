@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+/// Something you can ask breaks to do from outside its window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// You did what we prompted.
+    Done,
+    /// Put off the prompt.
+    Delay(Duration),
+    Focus(Duration),
+    /// Start counting a new day.
+    ResetDay,
+}
@@ -0,0 +1,199 @@
+//! Status for waybar, i3blocks, tmux and shell prompts, from the status file
+//! the running `breaks` keeps up to date.
+
+use crate::hours;
+use crate::lang::{Language, Message};
+use crate::snapshot::Snapshot;
+use serde::Serialize;
+use std::io::Write;
+use std::time::Duration;
+
+/// How soon a break or the end of the workday is when the bar warns you.
+const WARN_WITHIN: Duration = Duration::from_secs(5 * 60);
+/// How often to look at the status file.
+const POLL_EVERY: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object per line, with CSS classes.
+    Waybar,
+    /// Just the text, one line at a time, for i3blocks' `interval=persist`.
+    Text,
+}
+
+/// What the bar shows, in waybar's terms.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct Line {
+    text: String,
+    tooltip: String,
+    class: Vec<&'static str>,
+}
+
+fn line(snapshot: Option<&Snapshot>) -> Line {
+    let Some(s) = snapshot else {
+        let text = Language::default().text(Message::NotRunning);
+        return Line {
+            tooltip: text.clone(),
+            text,
+            class: vec!["stopped"],
+        };
+    };
+    let mut text = format!("{} / {}", hours::clock(s.worked), short(s.workday));
+    let mut class = Vec::new();
+    if let Some(prompt) = &s.prompt {
+        text = format!("{text} · {prompt}");
+        class.push("prompting");
+    } else if let Some(next) = &s.next_break {
+        text = format!(
+            "{text} · {}",
+            s.language.text(Message::NextBreak(&next.name, next.due_in))
+        );
+        if next.due_in < WARN_WITHIN {
+            class.push("warning");
+        }
+    }
+    if s.over_workday() {
+        class.push("over-workday");
+    } else if s.remaining < WARN_WITHIN && !class.contains(&"warning") {
+        class.push("warning");
+    }
+    Line {
+        text,
+        tooltip: s.language.text(Message::WorkingFor(s.worked)),
+        class,
+    }
+}
+
+/// Prints a line each time the running `breaks` updates its status, until
+/// whatever is reading stops.
+pub fn run(format: Format) -> anyhow::Result<()> {
+    let mut shown = None;
+    loop {
+        // A status file we can't read is as good as none.
+        let snapshot = Snapshot::read().ok().flatten();
+        let updated = snapshot.as_ref().map(|s| s.updated);
+        let line = line(snapshot.as_ref());
+        if shown.as_ref() != Some(&(updated, line.clone())) {
+            let mut out = std::io::stdout().lock();
+            match format {
+                Format::Waybar => writeln!(out, "{}", serde_json::to_string(&line)?)?,
+                Format::Text => writeln!(out, "{}", line.text)?,
+            }
+            out.flush()?;
+            shown = Some((updated, line));
+        }
+        std::thread::sleep(POLL_EVERY);
+    }
+}
+
+/// How to colour a prompt segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    None,
+    /// Terminal escapes, for a shell prompt.
+    Ansi,
+    /// Style markers, for tmux's status line.
+    Tmux,
+}
+
+/// A few characters for a tmux status line or shell prompt, such as "5:12/8h",
+/// with a "!" while there's a prompt.  This is empty if `breaks` isn't
+/// running, so it doesn't clutter the prompt.
+pub fn segment(snapshot: Option<&Snapshot>, colour: Colour) -> String {
+    let Some(s) = snapshot else {
+        return String::new();
+    };
+    let mut text = format!("{}/{}", hours::clock(s.worked), short(s.workday));
+    if s.prompt.is_some() {
+        text.push('!');
+    }
+    let (ansi, tmux) = if s.over_workday() {
+        ("31", "red")
+    } else if s.prompt.is_some() {
+        ("33", "yellow")
+    } else {
+        return text;
+    };
+    match colour {
+        Colour::None => text,
+        Colour::Ansi => format!("\x1b[{ansi}m{text}\x1b[0m"),
+        Colour::Tmux => format!("#[fg={tmux}]{text}#[default]"),
+    }
+}
+
+/// The length of the workday, as in "8h" or "7:30".
+fn short(workday: Duration) -> String {
+    if workday.as_secs().is_multiple_of(3600) {
+        format!("{}h", workday.as_secs() / 3600)
+    } else {
+        hours::clock(workday)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::snapshot::NextBreak;
+
+    #[test]
+    fn lines() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let mut s = Snapshot {
+            worked: minutes(5 * 60 + 12),
+            remaining: minutes(2 * 60 + 48),
+            workday: minutes(8 * 60),
+            next_break: Some(NextBreak {
+                name: "stretch".to_string(),
+                due_in: minutes(23),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            line(Some(&s)),
+            Line {
+                text: "5:12 / 8h · next: stretch in 0:23".to_string(),
+                tooltip: "You've been working for 5:12".to_string(),
+                class: vec![],
+            }
+        );
+
+        s.next_break.as_mut().unwrap().due_in = minutes(3);
+        assert_eq!(line(Some(&s)).class, vec!["warning"]);
+
+        s.next_break = None;
+        s.prompt = Some("Stretch".to_string());
+        s.worked = minutes(9 * 60 + 12);
+        s.remaining = Duration::ZERO;
+        s.workday = minutes(7 * 60 + 30);
+        let l = line(Some(&s));
+        assert_eq!(l.text, "9:12 / 7:30 · Stretch");
+        assert_eq!(l.class, vec!["prompting", "over-workday"]);
+        assert_eq!(
+            serde_json::to_string(&l).unwrap(),
+            r#"{"text":"9:12 / 7:30 · Stretch","tooltip":"You've been working for 9:12","class":["prompting","over-workday"]}"#
+        );
+
+        assert_eq!(line(None).class, vec!["stopped"]);
+    }
+
+    #[test]
+    fn segments() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let mut s = Snapshot {
+            worked: minutes(5 * 60 + 12),
+            workday: minutes(8 * 60),
+            ..Default::default()
+        };
+        assert_eq!(segment(Some(&s), Colour::Ansi), "5:12/8h");
+        s.prompt = Some("Stretch".to_string());
+        assert_eq!(segment(Some(&s), Colour::None), "5:12/8h!");
+        assert_eq!(segment(Some(&s), Colour::Ansi), "\x1b[33m5:12/8h!\x1b[0m");
+        s.prompt = None;
+        s.worked = minutes(9 * 60 + 12);
+        assert_eq!(
+            segment(Some(&s), Colour::Tmux),
+            "#[fg=red]9:12/8h#[default]"
+        );
+        assert_eq!(segment(None, Colour::Tmux), "");
+    }
+}
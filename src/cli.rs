@@ -0,0 +1,122 @@
+use crate::bar;
+use crate::hours::{self, Pretty};
+use crate::inbox::{self, Request};
+use crate::lang::{Language, Message};
+use crate::snapshot::Snapshot;
+
+const USAGE: &str = "usage:
+    breaks                            run breaks
+    breaks tui                        run breaks in the terminal, rather than a window
+    breaks add <time> [reason]        count time worked away from the computer
+    breaks subtract <time> [reason]   don't count time at the computer that wasn't work
+    breaks personal [on|off]          toggle whether your screen time is personal
+    breaks bar [--text]               keep printing status for waybar, or as text for i3blocks
+    breaks prompt-segment [--color|--tmux]
+                                      print a short status for a shell prompt or tmux
+    breaks check [--over-workday] [--prompt-pending]
+                                      fail if you're past your workday or ignoring a prompt";
+
+/// Runs `breaks` with command line arguments `args` (not including the program name).
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    match args[0].as_str() {
+        "add" | "subtract" => {
+            let time = args
+                .get(1)
+                .ok_or_else(|| anyhow::anyhow!("How much time?\n{USAGE}"))?;
+            let duration = hours::parseme(time)
+                .map_err(|_| anyhow::anyhow!("I don't understand the time {time:?}"))?;
+            anyhow::ensure!(
+                duration <= inbox::MOST_AT_ONCE,
+                "You can {} at most {} at once",
+                args[0],
+                inbox::MOST_AT_ONCE.pretty()
+            );
+            let reason = args[2..].join(" ");
+            let request = if args[0] == "add" {
+                Request::Add { duration, reason }
+            } else {
+                Request::Subtract { duration, reason }
+            };
+            inbox::send(&request)?;
+            println!(
+                "Asked breaks to {} {}.  This takes effect when it next checks on you.",
+                args[0],
+                duration.pretty()
+            );
+            Ok(())
+        }
+        "personal" => {
+            let on = match args.get(1).map(String::as_str) {
+                None => None,
+                Some("on") => Some(true),
+                Some("off") => Some(false),
+                Some(other) => anyhow::bail!("Personal time {other:?}?\n{USAGE}"),
+            };
+            inbox::send(&Request::Personal { on })?;
+            match on {
+                None => println!("Asked breaks to toggle personal time."),
+                Some(true) => println!("Asked breaks to start personal time."),
+                Some(false) => println!("Asked breaks to stop personal time."),
+            }
+            Ok(())
+        }
+        "bar" => match args.get(1).map(String::as_str) {
+            None => bar::run(bar::Format::Waybar),
+            Some("--text") => bar::run(bar::Format::Text),
+            Some(other) => anyhow::bail!("Unknown option {other:?}\n{USAGE}"),
+        },
+        "prompt-segment" => {
+            let colour = match args.get(1).map(String::as_str) {
+                None => bar::Colour::None,
+                Some("--color") => bar::Colour::Ansi,
+                Some("--tmux") => bar::Colour::Tmux,
+                Some(other) => anyhow::bail!("Unknown option {other:?}\n{USAGE}"),
+            };
+            // An error in your shell prompt would be worse than nothing.
+            let snapshot = Snapshot::read().ok().flatten();
+            println!("{}", bar::segment(snapshot.as_ref(), colour));
+            Ok(())
+        }
+        "check" => {
+            let (mut over_workday, mut prompt_pending) = (false, false);
+            for arg in &args[1..] {
+                match arg.as_str() {
+                    "--over-workday" => over_workday = true,
+                    "--prompt-pending" => prompt_pending = true,
+                    other => anyhow::bail!("Unknown option {other:?}\n{USAGE}"),
+                }
+            }
+            if !over_workday && !prompt_pending {
+                (over_workday, prompt_pending) = (true, true);
+            }
+            let Some(s) = Snapshot::read()? else {
+                eprintln!("{}", Language::default().text(Message::NotRunning));
+                std::process::exit(2);
+            };
+            let mut failed = false;
+            if over_workday && s.over_workday() {
+                eprintln!(
+                    "{}",
+                    s.language.text(Message::OverWorkday(s.worked, s.workday))
+                );
+                failed = true;
+            }
+            match &s.prompt {
+                Some(p) if prompt_pending => {
+                    eprintln!("{}", s.language.text(Message::PromptPending(p)));
+                    failed = true;
+                }
+                _ => (),
+            }
+            if failed {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        "help" | "-h" | "--help" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        cmd => Err(anyhow::anyhow!("Unknown command {cmd:?}\n{USAGE}")),
+    }
+}
@@ -0,0 +1,259 @@
+//! A web page showing how your day is going, served on localhost.
+
+use crate::action::Action;
+use crate::history::{self, Entry, Kind};
+use crate::localhost;
+use crate::snapshot::Snapshot;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response};
+
+const PAGE: &str = include_str!("dashboard.html");
+
+/// Serves the dashboard, keeping it up to date with snapshots.
+#[derive(Clone)]
+pub struct Dashboard {
+    snapshot: Arc<Mutex<Snapshot>>,
+    /// Where the history is kept.
+    history: PathBuf,
+}
+
+impl Dashboard {
+    /// A dashboard showing the history kept at `history`.
+    pub fn new(history: PathBuf) -> Self {
+        Dashboard {
+            snapshot: Default::default(),
+            history,
+        }
+    }
+
+    pub fn update(&self, snapshot: &Snapshot) {
+        *self.snapshot.lock().unwrap() = snapshot.clone();
+    }
+
+    /// Serves the dashboard on `port` of localhost in the background, calling
+    /// `act` for its buttons, and returning the port, which is chosen for us
+    /// if `port` is 0.
+    pub fn serve(&self, port: u16, act: impl Fn(Action) + Send + 'static) -> anyhow::Result<u16> {
+        let server =
+            tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| anyhow::anyhow!("{e}"))?;
+        let port = server.server_addr().to_ip().map_or(port, |a| a.port());
+        let dashboard = self.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let snapshot = dashboard.snapshot.lock().unwrap().clone();
+                respond(request, port, &snapshot, &dashboard.history, &act);
+            }
+        });
+        Ok(port)
+    }
+}
+
+fn json(value: serde_json::Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(value.to_string())
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn respond(
+    mut request: Request,
+    port: u16,
+    snapshot: &Snapshot,
+    history: &Path,
+    act: &dyn Fn(Action),
+) {
+    if !localhost::for_us(&request, port) {
+        localhost::refuse(request);
+        return;
+    }
+    let (path, query) = request
+        .url()
+        .split_once('?')
+        .map_or((request.url(), ""), |(p, q)| (p, q));
+    let (path, query) = (path.to_string(), query.to_string());
+    let response = match (request.method(), path.as_str()) {
+        (Method::Get, "/") => Response::from_string(PAGE)
+            .with_header(Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap()),
+        (Method::Get, "/status") => json(status(snapshot, history)),
+        (Method::Get, "/history") => {
+            let days = query
+                .strip_prefix("days=")
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(7)
+                .clamp(1, 366);
+            json(days_worked(history, days))
+        }
+        (Method::Post, "/done" | "/snooze") => {
+            // Only our page can send JSON here, as other sites would need to
+            // ask first, so nobody else can press the buttons.
+            let is_json = request.headers().iter().any(|h| {
+                h.field.equiv("Content-Type") && h.value.as_str().starts_with("application/json")
+            });
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).ok();
+            let action = if path == "/done" {
+                Some(Action::Done)
+            } else {
+                serde_json::from_str::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|b| b["seconds"].as_u64())
+                    .map(|secs| Action::Delay(Duration::from_secs(secs)))
+            };
+            match action {
+                Some(action) if is_json => {
+                    act(action);
+                    Response::from_string("").with_status_code(204)
+                }
+                _ => Response::from_string("Bad request").with_status_code(400),
+            }
+        }
+        _ => Response::from_string("Not found").with_status_code(404),
+    };
+    request.respond(response).ok();
+}
+
+/// How things stand, with today's timeline.
+fn status(s: &Snapshot, history: &Path) -> serde_json::Value {
+    let now = chrono::Local::now();
+    let entries = history::read(history).unwrap_or_else(|e| {
+        eprintln!("Unable to read history: {e}");
+        Vec::new()
+    });
+    let mut timeline: Vec<serde_json::Value> = entries
+        .iter()
+        .filter(|e| e.start.date_naive() == now.date_naive() && e.duration > Duration::ZERO)
+        .map(stretch)
+        .collect();
+    // The stretch we're in the middle of isn't in the history yet.
+    let current = Entry {
+        start: s.since,
+        kind: if s.idle { Kind::Break } else { Kind::Work },
+        duration: (now - s.since).to_std().unwrap_or_default(),
+        note: String::new(),
+    };
+    timeline.push(stretch(&current));
+    serde_json::json!({
+        "worked": s.worked.as_secs(),
+        "workday": s.workday.as_secs(),
+        "idle": s.idle,
+        "prompt": s.prompt,
+        "breaks": s.breaks.iter().map(|b| serde_json::json!({
+            "name": b.name,
+            "due_in": b.due_in.as_secs(),
+        })).collect::<Vec<_>>(),
+        "timeline": timeline,
+        "now": now,
+        "running": s.is_current(now),
+    })
+}
+
+fn stretch(e: &Entry) -> serde_json::Value {
+    serde_json::json!({
+        "start": e.start,
+        "kind": e.kind,
+        "seconds": e.duration.as_secs(),
+    })
+}
+
+/// Time worked on each of the last `days` days.
+fn days_worked(history: &Path, days: u32) -> serde_json::Value {
+    let entries = history::read(history).unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+    history::daily(&entries, days, today)
+        .into_iter()
+        .map(|(date, worked)| serde_json::json!({"date": date, "worked": worked.as_secs()}))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    #[test]
+    fn dashboard() {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let history = std::env::temp_dir().join(format!(
+            "breaks-test-dashboard-{}.jsonl",
+            std::process::id()
+        ));
+        let start = Instant::now() - Duration::from_secs(10 * 60);
+        history::append(
+            &history,
+            &Entry::new(Kind::Work, start, Duration::from_secs(5 * 60)),
+        )
+        .unwrap();
+        let dashboard = Dashboard::new(history.clone());
+        dashboard.update(&Snapshot {
+            worked: Duration::from_secs(3600),
+            prompt: Some("Stretch".to_string()),
+            updated: chrono::Local::now(),
+            ..Default::default()
+        });
+        let port = dashboard
+            .serve(0, move |a| {
+                tx.lock().unwrap().send(a).unwrap();
+            })
+            .unwrap();
+        let url = |path: &str| format!("http://127.0.0.1:{port}{path}");
+        let get = |path: &str| -> serde_json::Value {
+            serde_json::from_str(&ureq::get(&url(path)).call().unwrap().into_string().unwrap())
+                .unwrap()
+        };
+
+        let page = ureq::get(&url("/")).call().unwrap().into_string().unwrap();
+        assert!(page.contains("<html"));
+        let page = ureq::get(&url("/"))
+            .set("Host", &format!("localhost:{port}"))
+            .call()
+            .unwrap()
+            .into_string()
+            .unwrap();
+        assert!(page.contains("<html"));
+        let status = get("/status");
+        assert_eq!(status["worked"], 3600);
+        assert_eq!(status["prompt"], "Stretch");
+        assert_eq!(status["running"], true);
+        let days = get("/history?days=30");
+        assert_eq!(days.as_array().unwrap().len(), 30);
+        let worked: u64 = days
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["worked"].as_u64().unwrap())
+            .sum();
+        assert_eq!(worked, 5 * 60);
+        std::fs::remove_file(&history).ok();
+
+        let post = |path: &str, body: &str| {
+            ureq::post(&url(path))
+                .set("Content-Type", "application/json")
+                .send_string(body)
+                .ok()
+                .map(|r| r.status())
+        };
+        assert_eq!(post("/snooze", r#"{"seconds": 900}"#), Some(204));
+        assert_eq!(post("/done", "{}"), Some(204));
+        // Nor can another site, whose name leads here, read anything.
+        let elsewhere = |path: &str| {
+            ureq::get(&url(path))
+                .set("Host", &format!("example.com:{port}"))
+                .call()
+                .map_err(|e| e.into_response().map(|r| r.status()))
+                .map(|r| r.status())
+        };
+        assert_eq!(elsewhere("/status"), Err(Some(403)));
+        assert_eq!(elsewhere("/"), Err(Some(403)));
+        // A form on some other site can't press the buttons.
+        assert!(ureq::post(&url("/done")).send_string("").is_err());
+        let wait = Duration::from_secs(5);
+        assert_eq!(
+            rx.recv_timeout(wait),
+            Ok(Action::Delay(Duration::from_secs(900)))
+        );
+        assert_eq!(rx.recv_timeout(wait), Ok(Action::Done));
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+}
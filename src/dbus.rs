@@ -0,0 +1,196 @@
+use crate::action::Action;
+use crate::snapshot::Snapshot;
+use std::time::Duration;
+
+pub const NAME: &str = "org.breaks.Breaks";
+pub const PATH: &str = "/org/breaks/Breaks";
+
+/// The `org.breaks.Breaks` interface.
+struct Service {
+    snapshot: Snapshot,
+    act: Box<dyn Fn(Action) + Send + Sync>,
+}
+
+#[zbus::interface(name = "org.breaks.Breaks")]
+impl Service {
+    /// Seconds worked today.
+    #[zbus(property)]
+    fn work_time(&self) -> u64 {
+        self.snapshot.worked.as_secs()
+    }
+    /// Seconds left of the workday.
+    #[zbus(property)]
+    fn remaining(&self) -> u64 {
+        self.snapshot.remaining.as_secs()
+    }
+    #[zbus(property)]
+    fn idle(&self) -> bool {
+        self.snapshot.idle
+    }
+    /// The current prompt, or "" if there isn't one.
+    #[zbus(property)]
+    fn prompt(&self) -> String {
+        self.snapshot.prompt.clone().unwrap_or_default()
+    }
+
+    fn done(&self) {
+        (self.act)(Action::Done);
+    }
+    fn snooze(&self, seconds: u64) {
+        (self.act)(Action::Delay(Duration::from_secs(seconds)));
+    }
+    fn focus(&self, seconds: u64) {
+        (self.act)(Action::Focus(Duration::from_secs(seconds)));
+    }
+    fn reset_day(&self) {
+        (self.act)(Action::ResetDay);
+    }
+
+    /// We're prompting you.
+    #[zbus(signal)]
+    async fn prompted(ctxt: &zbus::SignalContext<'_>, prompt: &str) -> zbus::Result<()>;
+    /// You came back after being away for `seconds`.
+    #[zbus(signal)]
+    async fn resumed(ctxt: &zbus::SignalContext<'_>, seconds: u64) -> zbus::Result<()>;
+}
+
+/// Our service on the session bus.
+#[derive(Clone)]
+pub struct Server {
+    connection: zbus::blocking::Connection,
+}
+
+impl Server {
+    /// Publishes the service on the session bus, calling `act` when asked
+    /// to do something.
+    pub fn new(act: impl Fn(Action) + Send + Sync + 'static) -> zbus::Result<Self> {
+        Server::start(zbus::blocking::connection::Builder::session()?, act)
+    }
+
+    fn start(
+        builder: zbus::blocking::connection::Builder,
+        act: impl Fn(Action) + Send + Sync + 'static,
+    ) -> zbus::Result<Self> {
+        let service = Service {
+            snapshot: Snapshot::default(),
+            act: Box::new(act),
+        };
+        let connection = builder.name(NAME)?.serve_at(PATH, service)?.build()?;
+        Ok(Server { connection })
+    }
+
+    fn interface(&self) -> zbus::Result<zbus::blocking::object_server::InterfaceRef<Service>> {
+        self.connection.object_server().interface(PATH)
+    }
+
+    /// Updates the properties, letting anyone watching know what changed.
+    pub fn update(&self, snapshot: &Snapshot) -> zbus::Result<()> {
+        let iface = self.interface()?;
+        let old = std::mem::replace(&mut iface.get_mut().snapshot, snapshot.clone());
+        let service = iface.get();
+        let ctxt = iface.signal_context();
+        zbus::block_on(async {
+            if old.worked != snapshot.worked {
+                service.work_time_changed(ctxt).await?;
+            }
+            if old.remaining != snapshot.remaining {
+                service.remaining_changed(ctxt).await?;
+            }
+            if old.idle != snapshot.idle {
+                service.idle_changed(ctxt).await?;
+            }
+            if old.prompt != snapshot.prompt {
+                service.prompt_changed(ctxt).await?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn prompted(&self, prompt: &str) -> zbus::Result<()> {
+        zbus::block_on(Service::prompted(
+            self.interface()?.signal_context(),
+            prompt,
+        ))
+    }
+
+    pub fn resumed(&self, away: Duration) -> zbus::Result<()> {
+        zbus::block_on(Service::resumed(
+            self.interface()?.signal_context(),
+            away.as_secs(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testbus::PrivateBus;
+    use std::sync::{mpsc, Mutex};
+
+    #[zbus::proxy(
+        interface = "org.breaks.Breaks",
+        default_service = "org.breaks.Breaks",
+        default_path = "/org/breaks/Breaks"
+    )]
+    trait Breaks {
+        #[zbus(property)]
+        fn work_time(&self) -> zbus::Result<u64>;
+        #[zbus(property)]
+        fn idle(&self) -> zbus::Result<bool>;
+        #[zbus(property)]
+        fn prompt(&self) -> zbus::Result<String>;
+        fn done(&self) -> zbus::Result<()>;
+        fn snooze(&self, seconds: u64) -> zbus::Result<()>;
+        fn reset_day(&self) -> zbus::Result<()>;
+        #[zbus(signal)]
+        fn prompted(&self, prompt: String) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn service() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("No dbus-daemon, skipping");
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let server = Server::start(bus.builder(), move |a| {
+            tx.lock().unwrap().send(a).unwrap();
+        })
+        .unwrap();
+        let client = BreaksProxyBlocking::builder(&bus.builder().build().unwrap())
+            .cache_properties(zbus::CacheProperties::No)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.work_time().unwrap(), 0);
+        server
+            .update(&Snapshot {
+                worked: Duration::from_secs(3 * 60 * 60),
+                remaining: Duration::from_secs(5 * 60 * 60),
+                idle: false,
+                prompt: Some("Stretch".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(client.work_time().unwrap(), 3 * 60 * 60);
+        assert!(!client.idle().unwrap());
+        assert_eq!(client.prompt().unwrap(), "Stretch");
+
+        let wait = Duration::from_secs(5);
+        client.done().unwrap();
+        assert_eq!(rx.recv_timeout(wait), Ok(Action::Done));
+        client.snooze(900).unwrap();
+        assert_eq!(
+            rx.recv_timeout(wait),
+            Ok(Action::Delay(Duration::from_secs(900)))
+        );
+        client.reset_day().unwrap();
+        assert_eq!(rx.recv_timeout(wait), Ok(Action::ResetDay));
+
+        let mut prompts = client.receive_prompted().unwrap();
+        server.prompted("Walk").unwrap();
+        let signal = prompts.next().unwrap();
+        assert_eq!(signal.args().unwrap().prompt(), "Walk");
+    }
+}
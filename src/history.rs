@@ -0,0 +1,168 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// What a stretch of time in the history was spent on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Time at the computer.
+    Work,
+    /// Time away from the computer.
+    Break,
+    /// Time away from the computer that was spent in a meeting.
+    Meeting,
+    /// Time away from the computer that was spent working.
+    OfflineWork,
+    /// Work time added by hand.
+    Added,
+    /// Time at the computer that wasn't work.
+    Subtracted,
+    /// Time at the computer in personal-time mode.
+    Personal,
+    /// You said you did what we prompted you to, which is the note.
+    Done,
+}
+
+/// One line of the history.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Entry {
+    /// When this started.
+    pub start: DateTime<Local>,
+    pub kind: Kind,
+    #[serde(with = "crate::hours")]
+    pub duration: Duration,
+    /// Why, if we were told.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl Entry {
+    pub fn new(kind: Kind, start: Instant, duration: Duration) -> Self {
+        Entry {
+            start: local_time(start),
+            kind,
+            duration,
+            note: String::new(),
+        }
+    }
+    pub fn noted(self, note: String) -> Self {
+        Entry { note, ..self }
+    }
+}
+
+/// The wall clock time of `t`.
+pub fn local_time(t: Instant) -> DateTime<Local> {
+    let now = Instant::now();
+    let local = Local::now();
+    if t > now {
+        local + chrono::Duration::from_std(t - now).unwrap_or_else(|_| chrono::Duration::zero())
+    } else {
+        local - chrono::Duration::from_std(now - t).unwrap_or_else(|_| chrono::Duration::zero())
+    }
+}
+
+pub fn history_path() -> std::path::PathBuf {
+    crate::config_path("breaks-history.jsonl")
+}
+
+/// The whole history at `path`, skipping any lines we can't make sense of.
+pub fn read(path: &std::path::Path) -> anyhow::Result<Vec<Entry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// Adds `entry` to the end of the history at `path`.
+pub fn append(path: &std::path::Path, entry: &Entry) -> anyhow::Result<()> {
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(f, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// How long you worked on each of the `days` days up to `today`, counting
+/// meetings and time added by hand, and not time subtracted.
+pub fn daily(entries: &[Entry], days: u32, today: NaiveDate) -> Vec<(NaiveDate, Duration)> {
+    let first = today - chrono::Days::new(days.saturating_sub(1) as u64);
+    let mut worked: Vec<(NaiveDate, Duration)> = first
+        .iter_days()
+        .take(days as usize)
+        .map(|d| (d, Duration::ZERO))
+        .collect();
+    let mut subtracted = vec![Duration::ZERO; worked.len()];
+    for e in entries {
+        let date = e.start.date_naive();
+        if date < first || date > today {
+            continue;
+        }
+        let i = (date - first).num_days() as usize;
+        match e.kind {
+            Kind::Work | Kind::Meeting | Kind::OfflineWork | Kind::Added => {
+                worked[i].1 += e.duration
+            }
+            Kind::Subtracted => subtracted[i] += e.duration,
+            _ => (),
+        }
+    }
+    for ((_, w), s) in worked.iter_mut().zip(subtracted) {
+        *w = w.saturating_sub(s);
+    }
+    worked
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let e = Entry::new(
+            Kind::OfflineWork,
+            Instant::now(),
+            Duration::from_secs(45 * 60),
+        );
+        let json = serde_json::to_string(&e).unwrap();
+        assert!(json.contains(r#""kind":"offline_work""#));
+        assert!(json.contains(r#""duration":"45 minutes""#));
+        assert_eq!(serde_json::from_str::<Entry>(&json).unwrap(), e);
+    }
+
+    #[test]
+    fn days() {
+        use chrono::TimeZone;
+        let entry = |day, hours: u64, kind| Entry {
+            start: Local.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap(),
+            kind,
+            duration: Duration::from_secs(hours * 60 * 60),
+            note: String::new(),
+        };
+        let entries = [
+            entry(1, 3, Kind::Work),
+            entry(3, 2, Kind::Work),
+            entry(3, 1, Kind::Meeting),
+            entry(3, 1, Kind::Break),
+            entry(4, 1, Kind::Work),
+            entry(4, 2, Kind::Subtracted),
+        ];
+        let today = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let hours = |h: u64| Duration::from_secs(h * 60 * 60);
+        assert_eq!(
+            daily(&entries, 3, today),
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(), hours(0)),
+                (NaiveDate::from_ymd_opt(2024, 3, 3).unwrap(), hours(3)),
+                (today, hours(0)),
+            ]
+        );
+    }
+}
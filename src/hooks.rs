@@ -0,0 +1,189 @@
+//! Your own commands, run when things happen.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Something that can have a hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Event {
+    Prompt,
+    /// You came back after being away.
+    Resume,
+    /// You went past the end of your workday, once a day.
+    EndOfDay,
+    /// A new day started, or you reset the day.
+    NewDay,
+    /// A prompt has gone ignored for `when_to_lock_screen`.
+    Lock,
+}
+
+impl Event {
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::Prompt => "prompt",
+            Event::Resume => "resume",
+            Event::EndOfDay => "end-of-day",
+            Event::NewDay => "new-day",
+            Event::Lock => "lock",
+        }
+    }
+}
+
+/// What happened, as given to a hook.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Payload {
+    pub event: Event,
+    /// Seconds worked today, or on the day that just ended for `new-day`.
+    pub worked: u64,
+    /// Seconds in the workday.
+    pub workday: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Seconds away, for `resume`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub away: Option<u64>,
+}
+
+impl Payload {
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("BREAKS_EVENT", self.event.name().to_string()),
+            ("BREAKS_WORKED", self.worked.to_string()),
+            ("BREAKS_WORKDAY", self.workday.to_string()),
+        ];
+        if let Some(prompt) = &self.prompt {
+            env.push(("BREAKS_PROMPT", prompt.clone()));
+        }
+        if let Some(away) = self.away {
+            env.push(("BREAKS_AWAY", away.to_string()));
+        }
+        env
+    }
+}
+
+/// Shell commands to run for each event.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// How long a hook may run before we stop it.
+    #[serde(with = "crate::hours")]
+    timeout: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resume: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_of_day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lock: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            timeout: Duration::from_secs(10),
+            prompt: None,
+            resume: None,
+            end_of_day: None,
+            new_day: None,
+            lock: None,
+        }
+    }
+}
+
+impl Config {
+    fn command(&self, event: Event) -> Option<&str> {
+        match event {
+            Event::Prompt => self.prompt.as_deref(),
+            Event::Resume => self.resume.as_deref(),
+            Event::EndOfDay => self.end_of_day.as_deref(),
+            Event::NewDay => self.new_day.as_deref(),
+            Event::Lock => self.lock.as_deref(),
+        }
+    }
+
+    /// Starts the hook for `payload.event`, if there is one, without waiting
+    /// for it.  Failures are logged.
+    pub fn fire(&self, payload: Payload) {
+        let Some(command) = self.command(payload.event) else {
+            return;
+        };
+        let command = command.to_string();
+        let timeout = self.timeout;
+        std::thread::spawn(move || {
+            if let Err(e) = run(&command, &payload, timeout) {
+                eprintln!("The {} hook failed: {e}", payload.event.name());
+            }
+        });
+    }
+}
+
+/// Runs `command` with the shell, waiting up to `timeout` for it to finish.
+fn run(command: &str, payload: &Payload, timeout: Duration) -> anyhow::Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(payload.env())
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input is fine.
+        writeln!(stdin, "{}", serde_json::to_string(payload)?).ok();
+    }
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            anyhow::ensure!(status.success(), "{command:?} exited with {status}");
+            return Ok(());
+        }
+        if start.elapsed() > timeout {
+            child.kill().ok();
+            child.wait().ok();
+            anyhow::bail!("{command:?} took longer than {timeout:?}, so we stopped it");
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hooks() {
+        let out = std::env::temp_dir().join(format!("breaks-hook-{}", std::process::id()));
+        let payload = Payload {
+            event: Event::Resume,
+            worked: 3600,
+            workday: 8 * 3600,
+            prompt: None,
+            away: Some(600),
+        };
+        let timeout = Duration::from_secs(5);
+        let command = format!(
+            "cat > {0}; echo $BREAKS_EVENT $BREAKS_AWAY >> {0}",
+            out.display()
+        );
+        run(&command, &payload, timeout).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "{\"event\":\"resume\",\"worked\":3600,\"workday\":28800,\"away\":600}\nresume 600\n"
+        );
+        std::fs::remove_file(out).ok();
+
+        assert!(run("exit 3", &payload, timeout).is_err());
+        let start = Instant::now();
+        assert!(run("sleep 10", &payload, Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let config: Config = toml::from_str("new_day = 'true'\ntimeout = '30s'").unwrap();
+        assert_eq!(config.command(Event::NewDay), Some("true"));
+        assert_eq!(config.command(Event::Lock), None);
+        assert_eq!(config.timeout, Duration::from_secs(30));
+    }
+}
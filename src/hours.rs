@@ -114,28 +114,69 @@ impl<'a> ser::Serialize for Serde<&'a Duration> {
     }
 }
 
-fn parseme(v: &str) -> Result<Duration, ()> {
-    let mut hours = 0.0;
-    let mut minutes = 0.0;
-    if let Some((h, m)) = v.split_once(":") {
-        hours = h.trim().parse().map_err(|_| ())?;
-        minutes = m.trim().parse().map_err(|_| ())?;
-    } else if let Some(h) = v
-        .strip_suffix("h")
-        .or_else(|| v.strip_suffix("hours"))
-        .or_else(|| v.strip_suffix("hour"))
-    {
-        hours = h.trim().parse().map_err(|_| ())?;
-    } else if let Some(m) = v
-        .strip_suffix("m")
-        .or_else(|| v.strip_suffix("minutes"))
-        .or_else(|| v.strip_suffix("minute"))
+impl ser::Serialize for Serde<&Option<Duration>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
     {
-        minutes = m.trim().parse().map_err(|_| ())?;
-    } else {
-        return Err(());
+        match self.0 {
+            Some(d) => serializer.serialize_some(&Serde(d)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Units we understand, in English, German and Spanish, with how many
+/// seconds each is.  Units come before those that are a suffix of them.
+const UNITS: &[(&str, f64)] = &[
+    ("hours", 3600.0),
+    ("hour", 3600.0),
+    ("stunden", 3600.0),
+    ("stunde", 3600.0),
+    ("std", 3600.0),
+    ("horas", 3600.0),
+    ("hora", 3600.0),
+    ("h", 3600.0),
+    ("minutes", 60.0),
+    ("minute", 60.0),
+    ("minuten", 60.0),
+    ("minutos", 60.0),
+    ("minuto", 60.0),
+    ("min", 60.0),
+    ("m", 60.0),
+    ("seconds", 1.0),
+    ("second", 1.0),
+    ("sekunden", 1.0),
+    ("sekunde", 1.0),
+    ("segundos", 1.0),
+    ("segundo", 1.0),
+    ("s", 1.0),
+];
+
+pub fn parseme(v: &str) -> Result<Duration, ()> {
+    let number = |n: &str| {
+        n.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite() && *n >= 0.0)
+    };
+    if let Some((h, m)) = v.split_once(':') {
+        let hours = number(h).ok_or(())?;
+        let minutes = number(m).ok_or(())?;
+        return Duration::try_from_secs_f64((hours * 60.0 + minutes) * 60.0).map_err(|_| ());
     }
-    Ok(Duration::from_secs_f64((hours * 60.0 + minutes) * 60.0))
+    let v = v.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find_map(|(unit, secs)| v.strip_suffix(unit).and_then(number).map(|n| n * secs))
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or(())
+}
+
+/// Formats `d` like a clock, as in "0:23" or "5:12".
+pub fn clock(d: Duration) -> String {
+    let minutes = d.as_secs() / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
 }
 
 pub trait Pretty {
@@ -148,6 +189,8 @@ impl Pretty for Duration {
         let hours = minutes / 60;
         let minutes = minutes - hours * 60;
         match (hours, minutes) {
+            (0, 0) if secs == 1 => "1 second".to_string(),
+            (0, 0) if secs > 0 => format!("{secs} seconds"),
             (0, 0) => "0 minutes".to_string(),
             (1, 0) => format!("{hours} hour"),
             (_, 0) => format!("{hours} hours"),
@@ -158,6 +201,78 @@ impl Pretty for Duration {
     }
 }
 
+/// Formats a duration for text to speech, which would read "3:02" as a
+/// clock time.
+pub trait Spoken {
+    fn spoken(&self) -> String;
+}
+impl Spoken for Duration {
+    fn spoken(&self) -> String {
+        let secs = self.as_secs();
+        let minutes = secs / 60;
+        let hours = minutes / 60;
+        let minutes = minutes - hours * 60;
+        match (hours, minutes) {
+            (0, 0) if secs > 0 => count(secs, "second"),
+            (0, 0) => "zero minutes".to_string(),
+            (0, _) => count(minutes, "minute"),
+            (_, 0) => count(hours, "hour"),
+            _ => format!("{} and {}", count(hours, "hour"), count(minutes, "minute")),
+        }
+    }
+}
+
+/// `n` of `unit`, in words.
+fn count(n: u64, unit: &str) -> String {
+    if n == 1 {
+        format!("one {unit}")
+    } else {
+        format!("{} {unit}s", words(n))
+    }
+}
+
+/// `n` in words, for numbers small enough to be worth it.
+fn words(n: u64) -> String {
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+    let i = n as usize;
+    match n {
+        0..=19 => ONES[i].to_string(),
+        20..=99 => match i % 10 {
+            0 => TENS[i / 10].to_string(),
+            ones => format!("{}-{}", TENS[i / 10], ONES[ones]),
+        },
+        100..=999 => match n % 100 {
+            0 => format!("{} hundred", ONES[i / 100]),
+            rest => format!("{} hundred and {}", ONES[i / 100], words(rest)),
+        },
+        _ => n.to_string(),
+    }
+}
+
 impl ser::Serialize for Serde<Duration> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -185,10 +300,31 @@ mod test {
         assert_eq!(parseme("1:00").unwrap(), Duration::from_secs(60 * 60));
         assert_eq!(parseme("1 hour").unwrap(), Duration::from_secs(60 * 60));
         assert_eq!(parseme("2 minutes").unwrap(), Duration::from_secs(2 * 60));
+        assert_eq!(parseme("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parseme("45 seconds").unwrap(), Duration::from_secs(45));
+        assert_eq!(parseme("1 second").unwrap(), Duration::from_secs(1));
+        assert!(parseme("5 sheep").is_err());
+        assert!(parseme("-5 minutes").is_err());
+        assert!(parseme("1e300:00").is_err());
+        assert_eq!(
+            parseme("2 Stunden").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+        assert_eq!(parseme("1 Stunde").unwrap(), Duration::from_secs(60 * 60));
+        assert_eq!(parseme("30 minutos").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(
+            parseme("2 horas").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+        assert_eq!(parseme("5 Minuten").unwrap(), Duration::from_secs(5 * 60));
+        assert_eq!(parseme("20 segundos").unwrap(), Duration::from_secs(20));
     }
 
     #[test]
     fn ts() {
+        assert_eq!(Duration::from_secs(0).pretty().as_str(), "0 minutes");
+        assert_eq!(Duration::from_secs(1).pretty().as_str(), "1 second");
+        assert_eq!(Duration::from_secs(30).pretty().as_str(), "30 seconds");
         assert_eq!(Duration::from_secs(60).pretty().as_str(), "1 minute");
         assert_eq!(Duration::from_secs(2 * 60).pretty().as_str(), "2 minutes");
         assert_eq!(
@@ -201,6 +337,41 @@ mod test {
         );
     }
 
+    #[test]
+    fn spoken() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(Duration::from_secs(0).spoken(), "zero minutes");
+        assert_eq!(Duration::from_millis(500).spoken(), "zero minutes");
+        assert_eq!(Duration::from_secs(1).spoken(), "one second");
+        assert_eq!(Duration::from_secs(2).spoken(), "two seconds");
+        assert_eq!(Duration::from_secs(45).spoken(), "forty-five seconds");
+        assert_eq!(minutes(1).spoken(), "one minute");
+        assert_eq!(Duration::from_secs(61).spoken(), "one minute");
+        assert_eq!(minutes(2).spoken(), "two minutes");
+        assert_eq!(minutes(30).spoken(), "thirty minutes");
+        assert_eq!(minutes(59).spoken(), "fifty-nine minutes");
+        assert_eq!(minutes(60).spoken(), "one hour");
+        assert_eq!(minutes(61).spoken(), "one hour and one minute");
+        assert_eq!(minutes(62).spoken(), "one hour and two minutes");
+        assert_eq!(minutes(120).spoken(), "two hours");
+        assert_eq!(minutes(121).spoken(), "two hours and one minute");
+        assert_eq!(minutes(3 * 60 + 2).spoken(), "three hours and two minutes");
+        assert_eq!(
+            minutes(8 * 60 + 15).spoken(),
+            "eight hours and fifteen minutes"
+        );
+        assert_eq!(
+            minutes(11 * 60 + 40).spoken(),
+            "eleven hours and forty minutes"
+        );
+        assert_eq!(minutes(100 * 60).spoken(), "one hundred hours");
+        assert_eq!(
+            minutes(123 * 60).spoken(),
+            "one hundred and twenty-three hours"
+        );
+        assert_eq!(minutes(1234 * 60).spoken(), "1234 hours");
+    }
+
     #[test]
     fn with() {
         #[derive(Serialize, Deserialize)]
@@ -221,4 +392,22 @@ mod test {
         let reverse = serde_json::to_string(&foo).unwrap();
         assert_eq!(reverse, r#"{"time":"2 hours"}"#);
     }
+
+    #[test]
+    fn with_option() {
+        #[derive(Serialize, Deserialize)]
+        struct Foo {
+            #[serde(with = "super")]
+            time: Option<Duration>,
+        }
+
+        let json = r#"{"time": "30m"}"#;
+        let foo = serde_json::from_str::<Foo>(json).unwrap();
+        assert_eq!(foo.time, Some(Duration::from_secs(30 * 60)));
+        let reverse = serde_json::to_string(&foo).unwrap();
+        assert_eq!(reverse, r#"{"time":"30 minutes"}"#);
+
+        let foo = Foo { time: None };
+        assert_eq!(serde_json::to_string(&foo).unwrap(), r#"{"time":null}"#);
+    }
 }
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// A place we can ask how long the user has been idle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleSource {
+    /// The desktop (X11, Wayland, Mac, Windows) idle timer.
+    Desktop,
+    /// Access times of the terminals (ssh sessions, tmux panes, etc.) that
+    /// belong to this user.
+    Terminals,
+}
+
+impl IdleSource {
+    fn idle_time(self) -> anyhow::Result<Duration> {
+        match self {
+            IdleSource::Desktop => {
+                let idle = user_idle::UserIdle::get_time().map_err(|e| anyhow::anyhow!("{}", e))?;
+                Ok(idle.duration())
+            }
+            IdleSource::Terminals => terminal_idle_time(),
+        }
+    }
+}
+
+/// The idle time according to all of `sources`, which is the smallest idle
+/// time any of them reports.  A source that fails is ignored, unless they all
+/// fail.
+pub fn idle_time(sources: &[IdleSource]) -> anyhow::Result<Duration> {
+    let mut error = None;
+    let mut idle: Option<Duration> = None;
+    for s in sources {
+        match s.idle_time() {
+            Ok(t) => idle = Some(idle.map_or(t, |i| i.min(t))),
+            Err(e) => error = Some(e),
+        }
+    }
+    match (idle, error) {
+        (Some(t), _) => Ok(t),
+        (None, Some(e)) => Err(e),
+        (None, None) => Err(anyhow::anyhow!("No idle sources are configured")),
+    }
+}
+
+#[cfg(unix)]
+fn terminal_idle_time() -> anyhow::Result<Duration> {
+    use std::os::unix::fs::MetadataExt;
+
+    let me = std::fs::metadata("/proc/self")
+        .or_else(|_| std::fs::metadata(home::home_dir().unwrap_or_default()))?
+        .uid();
+    let mut ttys: Vec<std::path::PathBuf> = Vec::new();
+    if let Ok(utmp) = std::fs::read("/var/run/utmp") {
+        ttys.extend(
+            utmp_lines(&utmp)
+                .into_iter()
+                .map(|l| format!("/dev/{l}").into()),
+        );
+    }
+    for dir in ["/dev/pts", "/dev"] {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for e in entries.flatten() {
+                let name = e.file_name();
+                let name = name.to_string_lossy();
+                if dir == "/dev/pts" || name.starts_with("ttys") {
+                    ttys.push(e.path());
+                }
+            }
+        }
+    }
+    let last_input = ttys
+        .iter()
+        .filter_map(|t| std::fs::metadata(t).ok())
+        .filter(|m| m.uid() == me)
+        .filter_map(|m| m.accessed().ok())
+        .max()
+        .ok_or_else(|| anyhow::anyhow!("No terminals found"))?;
+    Ok(SystemTime::now()
+        .duration_since(last_input)
+        .unwrap_or(Duration::from_secs(0)))
+}
+
+#[cfg(not(unix))]
+fn terminal_idle_time() -> anyhow::Result<Duration> {
+    Err(anyhow::anyhow!(
+        "Terminal idle time is only supported on unix"
+    ))
+}
+
+/// Size of a glibc `struct utmp` record.
+const UTMP_SIZE: usize = 384;
+/// Offset and length of `ut_line` within a `struct utmp`.
+const UTMP_LINE: std::ops::Range<usize> = 8..40;
+/// The `ut_type` of a logged in user.
+const USER_PROCESS: i32 = 7;
+
+/// The terminals (like "pts/3") on which users are logged in, according to
+/// the contents of a utmp file.
+fn utmp_lines(utmp: &[u8]) -> Vec<String> {
+    utmp.chunks_exact(UTMP_SIZE)
+        .filter(|r| i32::from_ne_bytes([r[0], r[1], r[2], r[3]]) == USER_PROCESS)
+        .map(|r| {
+            let line = &r[UTMP_LINE];
+            let end = line.iter().position(|&b| b == 0).unwrap_or(line.len());
+            String::from_utf8_lossy(&line[..end]).into_owned()
+        })
+        .filter(|l| !l.is_empty() && !l.contains(".."))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(ut_type: i32, line: &str) -> Vec<u8> {
+        let mut r = vec![0; UTMP_SIZE];
+        r[..4].copy_from_slice(&ut_type.to_ne_bytes());
+        r[UTMP_LINE.start..UTMP_LINE.start + line.len()].copy_from_slice(line.as_bytes());
+        r
+    }
+
+    #[test]
+    fn utmp() {
+        let mut utmp = record(USER_PROCESS, "pts/3");
+        utmp.extend(record(8, "pts/4"));
+        utmp.extend(record(USER_PROCESS, "tty2"));
+        utmp.extend(record(USER_PROCESS, "../etc/passwd"));
+        assert_eq!(utmp_lines(&utmp), vec!["pts/3", "tty2"]);
+    }
+
+    #[test]
+    fn no_sources() {
+        assert!(idle_time(&[]).is_err());
+    }
+}
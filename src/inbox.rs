@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
+
+/// The most time you can add or subtract at once, which is a day.
+pub const MOST_AT_ONCE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Something the command line asks of the running `breaks`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", tag = "request")]
+pub enum Request {
+    /// Count time spent working away from the computer.
+    Add {
+        #[serde(with = "crate::hours")]
+        duration: Duration,
+        reason: String,
+    },
+    /// Don't count time spent at the computer that wasn't work.
+    Subtract {
+        #[serde(with = "crate::hours")]
+        duration: Duration,
+        reason: String,
+    },
+    /// Start or stop personal time, or toggle it if `on` is `None`.
+    Personal { on: Option<bool> },
+}
+
+pub fn inbox_path() -> std::path::PathBuf {
+    crate::config_path("breaks-inbox.jsonl")
+}
+
+/// Leaves `request` for the running `breaks` to pick up.
+pub fn send(request: &Request) -> anyhow::Result<()> {
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(inbox_path())?;
+    writeln!(f, "{}", serde_json::to_string(request)?)?;
+    Ok(())
+}
+
+/// Takes all the requests that have been sent.
+pub fn receive() -> anyhow::Result<Vec<Request>> {
+    let path = inbox_path();
+    let taken = path.with_extension("taken");
+    // Moving the inbox aside means nothing sent while we read gets lost.
+    match std::fs::rename(&path, &taken) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    }
+    let contents = std::fs::read_to_string(&taken)?;
+    std::fs::remove_file(&taken)?;
+    Ok(parse(&contents))
+}
+
+fn parse(contents: &str) -> Vec<Request> {
+    contents
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str(l) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                eprintln!("Ignoring bad request {l:?}: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn requests() {
+        let add = Request::Add {
+            duration: Duration::from_secs(45 * 60),
+            reason: "design review in room 3".to_string(),
+        };
+        let json = serde_json::to_string(&add).unwrap();
+        assert_eq!(
+            json,
+            r#"{"request":"add","duration":"45 minutes","reason":"design review in room 3"}"#
+        );
+        assert_eq!(
+            parse(&format!("{json}\ngarbage\n\n{json}\n")),
+            vec![add.clone(), add]
+        );
+    }
+}
@@ -0,0 +1,679 @@
+use crate::hours::{self, Pretty, Spoken};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A language we can talk to you in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+/// Everything we tell you, apart from the prompts you write yourself.
+#[derive(Clone, Copy, Debug)]
+pub enum Message<'a> {
+    CountingMeeting(Duration),
+    CountingOfflineWork(Duration),
+    GladOfBreak(Duration),
+    Added(Duration),
+    Subtracted(Duration),
+    PersonalOn,
+    PersonalOff,
+    FocusPastEndOfDay(Duration),
+    Focusing(Duration),
+    FocusOver,
+    WellDone(&'a str),
+    PuttingOff(&'a str),
+    PomodoroStarting(u32),
+    /// A pomodoro break is due, and whether it's a long one.
+    PomodoroBreak {
+        long: bool,
+        time: Duration,
+    },
+    PomodoroRested,
+    PomodoroSkipped,
+    PomodoroWaiting,
+    PomodoroWorking {
+        n: u32,
+        left: Duration,
+    },
+    PomodoroBreakTaken,
+    PomodoroResting {
+        long: bool,
+        left: Duration,
+    },
+    PomodorosDone(u32),
+    NiceMicrobreak,
+    /// A step of a routine, and how long to do it for.
+    Step(&'a str, Duration),
+    RoutineFinished,
+    RoutineSkipped,
+    RoutineStep {
+        n: usize,
+        of: usize,
+        text: &'a str,
+        left: Duration,
+    },
+    RoutineSummary {
+        steps: usize,
+        time: Duration,
+    },
+    PersonalAfk(Duration),
+    PersonalScreenTime(Duration),
+    PersonalSoFar {
+        personal: Duration,
+        worked: Duration,
+    },
+    WorkAfk(Duration),
+    /// The break's name is empty for a break without a prompt.
+    PostponingSeeAbove(&'a str),
+    PostponingWhileMeeting(&'a str),
+    PostponingFor(&'a str, Duration),
+    WorkingFor(Duration),
+    Resumed(Duration),
+    WelcomeBack,
+    NewDay,
+    /// You asked to start a new day.
+    DayReset,
+    /// For status bars: the next break, and how long until it's due.
+    NextBreak(&'a str, Duration),
+    NotRunning,
+    /// For `breaks check`: time worked, and the workday.
+    OverWorkday(Duration, Duration),
+    PromptPending(&'a str),
+    IdleFor(Duration),
+    AwayQuestion(Duration),
+    // Button labels.
+    Done,
+    Delay(Duration),
+    StartRoutine,
+    SkipRoutine,
+    PersonalTime,
+    BackToWork,
+    Focus(Duration),
+    EndFocus,
+    FocusAnyway,
+    Break,
+    Meeting,
+    OfflineWork,
+    Quit,
+}
+
+/// Picks the English, German or Spanish version.
+macro_rules! choose {
+    ($lang:expr, $en:expr, $de:expr, $es:expr) => {
+        match $lang {
+            Language::English => $en,
+            Language::German => $de,
+            Language::Spanish => $es,
+        }
+    };
+}
+
+/// Names of units, singular and plural.
+struct Units {
+    hour: [&'static str; 2],
+    minute: [&'static str; 2],
+    second: [&'static str; 2],
+    and: &'static str,
+}
+
+impl Language {
+    /// The language code, as used by text to speech voices.
+    pub fn code(self) -> &'static str {
+        choose!(self, "en", "de", "es")
+    }
+
+    fn units(self) -> Units {
+        choose!(
+            self,
+            Units {
+                hour: ["hour", "hours"],
+                minute: ["minute", "minutes"],
+                second: ["second", "seconds"],
+                and: "and",
+            },
+            Units {
+                hour: ["Stunde", "Stunden"],
+                minute: ["Minute", "Minuten"],
+                second: ["Sekunde", "Sekunden"],
+                and: "und",
+            },
+            Units {
+                hour: ["hora", "horas"],
+                minute: ["minuto", "minutos"],
+                second: ["segundo", "segundos"],
+                and: "y",
+            }
+        )
+    }
+
+    /// Like `Duration::pretty`, in this language.
+    pub fn duration(self, d: Duration) -> String {
+        if self == Language::English {
+            return d.pretty();
+        }
+        let units = self.units();
+        let count = |n: u64, unit: [&str; 2]| format!("{n} {}", unit[(n != 1) as usize]);
+        let secs = d.as_secs();
+        let (hours, minutes) = (secs / 3600, secs / 60 % 60);
+        match (hours, minutes) {
+            (0, 0) if secs > 0 => count(secs, units.second),
+            (0, _) => count(minutes, units.minute),
+            (_, 0) => count(hours, units.hour),
+            _ => format!("{hours}:{minutes:02}"),
+        }
+    }
+
+    /// Like `Duration::spoken`, in this language.  Other than in English,
+    /// we leave the numbers for the voice to read.
+    pub fn spoken_duration(self, d: Duration) -> String {
+        if self == Language::English {
+            return d.spoken();
+        }
+        let units = self.units();
+        let count = |n: u64, unit: [&str; 2]| format!("{n} {}", unit[(n != 1) as usize]);
+        let secs = d.as_secs();
+        let (hours, minutes) = (secs / 3600, secs / 60 % 60);
+        match (hours, minutes) {
+            (0, 0) if secs > 0 => count(secs, units.second),
+            (0, _) => count(minutes, units.minute),
+            (_, 0) => count(hours, units.hour),
+            _ => format!(
+                "{} {} {}",
+                count(hours, units.hour),
+                units.and,
+                count(minutes, units.minute)
+            ),
+        }
+    }
+
+    /// A clock time, the way it is usually written in this language.
+    pub fn clock(self, time: chrono::DateTime<chrono::Local>) -> String {
+        time.format(choose!(self, "%-I:%M %p", "%H:%M", "%H:%M"))
+            .to_string()
+    }
+
+    /// What to say at the end of the day, unless configured otherwise.
+    pub fn end_of_day_prompt(self) -> &'static str {
+        choose!(
+            self,
+            "End of day after {worked}",
+            "Feierabend nach {worked}",
+            "Fin de la jornada tras {worked}"
+        )
+    }
+
+    /// How to write `m`.
+    pub fn text(self, m: Message) -> String {
+        self.message(m, &|d| self.duration(d))
+    }
+
+    /// How to say `m`.
+    pub fn spoken(self, m: Message) -> String {
+        self.message(m, &|d| self.spoken_duration(d))
+    }
+
+    fn message(self, m: Message, d: &dyn Fn(Duration) -> String) -> String {
+        use Message::*;
+        let name = |n: &str| {
+            if n.is_empty() {
+                choose!(self, "your break", "deine Pause", "tu descanso").to_string()
+            } else {
+                n.to_string()
+            }
+        };
+        match m {
+            CountingMeeting(t) => choose!(
+                self,
+                format!("Counting your {} meeting as work.", d(t)),
+                format!("Dein Meeting von {} zählt als Arbeit.", d(t)),
+                format!("Tu reunión de {} cuenta como trabajo.", d(t))
+            ),
+            CountingOfflineWork(t) => choose!(
+                self,
+                format!("Counting {} of offline work.", d(t)),
+                format!("{} Offline-Arbeit gezählt.", d(t)),
+                format!("Se cuentan {} de trabajo sin conexión.", d(t))
+            ),
+            GladOfBreak(t) => choose!(
+                self,
+                format!("Glad you had a {} break.", d(t)),
+                format!("Schön, dass du {} Pause gemacht hast.", d(t)),
+                format!("Me alegra que hayas descansado {}.", d(t))
+            ),
+            Added(t) => choose!(
+                self,
+                format!("Added {}.", d(t)),
+                format!("{} hinzugefügt.", d(t)),
+                format!("Añadido: {}.", d(t))
+            ),
+            Subtracted(t) => choose!(
+                self,
+                format!("Subtracted {}.", d(t)),
+                format!("{} abgezogen.", d(t)),
+                format!("Restado: {}.", d(t))
+            ),
+            PersonalOn => choose!(
+                self,
+                "Enjoy your personal time!",
+                "Genieß deine Freizeit!",
+                "¡Disfruta de tu tiempo personal!"
+            )
+            .to_string(),
+            PersonalOff => choose!(
+                self,
+                "Back to work.",
+                "Zurück an die Arbeit.",
+                "De vuelta al trabajo."
+            )
+            .to_string(),
+            FocusPastEndOfDay(t) => choose!(
+                self,
+                format!(
+                    "Focusing for {} would take you past the end of your workday.",
+                    d(t)
+                ),
+                format!(
+                    "{} Fokuszeit würde über das Ende deines Arbeitstags hinausgehen.",
+                    d(t)
+                ),
+                format!(
+                    "Concentrarte durante {} te llevaría más allá del final de tu jornada.",
+                    d(t)
+                )
+            ),
+            Focusing(t) => choose!(
+                self,
+                format!("Focusing for {}.", d(t)),
+                format!("Fokus für {}.", d(t)),
+                format!("Concentración durante {}.", d(t))
+            ),
+            FocusOver => choose!(
+                self,
+                "Focus time is over.",
+                "Die Fokuszeit ist vorbei.",
+                "Se acabó el tiempo de concentración."
+            )
+            .to_string(),
+            WellDone(p) => choose!(
+                self,
+                format!("Well done with the {p}!"),
+                format!("Gut gemacht: {p}!"),
+                format!("¡Bien hecho: {p}!")
+            ),
+            PuttingOff(p) => choose!(
+                self,
+                format!("Putting off {p}..."),
+                format!("Verschiebe {p}..."),
+                format!("Posponiendo {p}...")
+            ),
+            PomodoroStarting(n) => choose!(
+                self,
+                format!("Starting pomodoro {n}."),
+                format!("Pomodoro {n} beginnt."),
+                format!("Empieza el pomodoro {n}.")
+            ),
+            PomodoroBreak { long: false, time } => choose!(
+                self,
+                format!("Pomodoro done!  Take a break for {}", d(time)),
+                format!("Pomodoro fertig!  Mach {} Pause", d(time)),
+                format!("¡Pomodoro terminado!  Descansa {}", d(time))
+            ),
+            PomodoroBreak { long: true, time } => choose!(
+                self,
+                format!("Pomodoros done!  Take a long break for {}", d(time)),
+                format!("Pomodoros fertig!  Mach eine lange Pause von {}", d(time)),
+                format!(
+                    "¡Pomodoros terminados!  Tómate un descanso largo de {}",
+                    d(time)
+                )
+            ),
+            PomodoroRested => choose!(
+                self,
+                "Break taken, welcome back!",
+                "Pause gemacht, willkommen zurück!",
+                "¡Descanso hecho, qué bueno verte de nuevo!"
+            )
+            .to_string(),
+            PomodoroSkipped => choose!(
+                self,
+                "You skipped your break.",
+                "Du hast deine Pause ausgelassen.",
+                "Te saltaste el descanso."
+            )
+            .to_string(),
+            PomodoroWaiting => choose!(
+                self,
+                "Pomodoro starts when you do",
+                "Der Pomodoro beginnt, wenn du anfängst",
+                "El pomodoro empieza cuando tú empiezas"
+            )
+            .to_string(),
+            PomodoroWorking { n, left } => choose!(
+                self,
+                format!("Pomodoro {n}: {} to go", d(left)),
+                format!("Pomodoro {n}: noch {}", d(left)),
+                format!("Pomodoro {n}: quedan {}", d(left))
+            ),
+            PomodoroBreakTaken => {
+                choose!(self, "Break taken", "Pause gemacht", "Descanso hecho").to_string()
+            }
+            PomodoroResting { long, left } => match (self, long) {
+                (Language::English, true) => format!("Long break: {} to go", d(left)),
+                (Language::English, false) => format!("Short break: {} to go", d(left)),
+                (Language::German, true) => format!("Lange Pause: noch {}", d(left)),
+                (Language::German, false) => format!("Kurze Pause: noch {}", d(left)),
+                (Language::Spanish, true) => format!("Descanso largo: quedan {}", d(left)),
+                (Language::Spanish, false) => format!("Descanso corto: quedan {}", d(left)),
+            },
+            PomodorosDone(n) => choose!(
+                self,
+                format!("({n} done today)"),
+                format!("({n} heute erledigt)"),
+                format!("({n} hechos hoy)")
+            ),
+            NiceMicrobreak => choose!(
+                self,
+                "Nice microbreak!",
+                "Schöne Mikropause!",
+                "¡Buena micropausa!"
+            )
+            .to_string(),
+            Step(text, t) => choose!(
+                self,
+                format!("{text} for {}", d(t)),
+                format!("{text} für {}", d(t)),
+                format!("{text} durante {}", d(t))
+            ),
+            RoutineFinished => choose!(
+                self,
+                "Routine finished!",
+                "Routine beendet!",
+                "¡Rutina terminada!"
+            )
+            .to_string(),
+            RoutineSkipped => choose!(
+                self,
+                "Skipped the routine.",
+                "Routine übersprungen.",
+                "Rutina omitida."
+            )
+            .to_string(),
+            RoutineStep { n, of, text, left } => choose!(
+                self,
+                format!("Step {n} of {of}: {text} ({} left)", d(left)),
+                format!("Schritt {n} von {of}: {text} (noch {})", d(left)),
+                format!("Paso {n} de {of}: {text} (quedan {})", d(left))
+            ),
+            RoutineSummary { steps, time } => choose!(
+                self,
+                format!("A routine of {steps} steps, taking {}", d(time)),
+                format!("Eine Routine mit {steps} Schritten, Dauer {}", d(time)),
+                format!("Una rutina de {steps} pasos, que dura {}", d(time))
+            ),
+            PersonalAfk(t) => choose!(
+                self,
+                format!("After {} of personal time you are now AFK!", d(t)),
+                format!("Nach {} Freizeit bist du jetzt weg!", d(t)),
+                format!("¡Tras {} de tiempo personal, ahora estás ausente!", d(t))
+            ),
+            PersonalScreenTime(t) => choose!(
+                self,
+                format!("That's {} of personal screen time today", d(t)),
+                format!("Das sind heute {} private Bildschirmzeit", d(t)),
+                format!(
+                    "Llevas {} de tiempo personal frente a la pantalla hoy",
+                    d(t)
+                )
+            ),
+            PersonalSoFar { personal, worked } => choose!(
+                self,
+                format!(
+                    "You've had {} of personal time (and worked {})",
+                    d(personal),
+                    d(worked)
+                ),
+                format!(
+                    "Du hattest {} Freizeit (und hast {} gearbeitet)",
+                    d(personal),
+                    d(worked)
+                ),
+                format!(
+                    "Has tenido {} de tiempo personal (y has trabajado {})",
+                    d(personal),
+                    d(worked)
+                )
+            ),
+            WorkAfk(t) => choose!(
+                self,
+                format!("After working {} you are now AFK!", d(t)),
+                format!("Nach {} Arbeit bist du jetzt weg!", d(t)),
+                format!("¡Tras trabajar {}, ahora estás ausente!", d(t))
+            ),
+            PostponingSeeAbove(n) => choose!(
+                self,
+                format!("Postponing {}, see above.", name(n)),
+                format!("Verschiebe {}, siehe oben.", name(n)),
+                format!("Posponiendo {}, mira arriba.", name(n))
+            ),
+            PostponingWhileMeeting(n) => choose!(
+                self,
+                format!("Postponing {} while you meet.", name(n)),
+                format!("Verschiebe {} während deines Meetings.", name(n)),
+                format!("Posponiendo {} mientras estás en una reunión.", name(n))
+            ),
+            PostponingFor(n, t) => choose!(
+                self,
+                format!("Postponing {} for {}.", name(n), d(t)),
+                format!("Verschiebe {} um {}.", name(n), d(t)),
+                format!("Posponiendo {} durante {}.", name(n), d(t))
+            ),
+            WorkingFor(t) => choose!(
+                self,
+                format!("You've been working for {}", d(t)),
+                format!("Du arbeitest seit {}", d(t)),
+                format!("Llevas {} trabajando", d(t))
+            ),
+            Resumed(t) => choose!(
+                self,
+                format!("You resumed working after a {} break.", d(t)),
+                format!("Du arbeitest wieder, nach {} Pause.", d(t)),
+                format!("Volviste a trabajar tras un descanso de {}.", d(t))
+            ),
+            WelcomeBack => choose!(
+                self,
+                "Welcome back!  Were you working?",
+                "Willkommen zurück!  Hast du gearbeitet?",
+                "¡Hola de nuevo!  ¿Estabas trabajando?"
+            )
+            .to_string(),
+            NewDay => choose!(
+                self,
+                "I think it is a new day.  Resetting.",
+                "Ich glaube, es ist ein neuer Tag.  Alles wird zurückgesetzt.",
+                "Creo que es un nuevo día.  Reiniciando."
+            )
+            .to_string(),
+            DayReset => choose!(
+                self,
+                "Starting a new day.",
+                "Ein neuer Tag beginnt.",
+                "Empieza un nuevo día."
+            )
+            .to_string(),
+            NextBreak(n, t) => choose!(
+                self,
+                format!("next: {} in {}", name(n), hours::clock(t)),
+                format!("als Nächstes: {} in {}", name(n), hours::clock(t)),
+                format!("siguiente: {} en {}", name(n), hours::clock(t))
+            ),
+            NotRunning => choose!(
+                self,
+                "breaks isn't running",
+                "breaks läuft nicht",
+                "breaks no está en marcha"
+            )
+            .to_string(),
+            OverWorkday(worked, workday) => choose!(
+                self,
+                format!(
+                    "You've worked {} today (limit {})",
+                    hours::clock(worked),
+                    d(workday)
+                ),
+                format!(
+                    "Du hast heute {} gearbeitet (Grenze {})",
+                    hours::clock(worked),
+                    d(workday)
+                ),
+                format!(
+                    "Hoy has trabajado {} (límite {})",
+                    hours::clock(worked),
+                    d(workday)
+                )
+            ),
+            PromptPending(p) => choose!(
+                self,
+                format!("You have a break waiting: {p}"),
+                format!("Eine Pause wartet auf dich: {p}"),
+                format!("Tienes un descanso pendiente: {p}")
+            ),
+            IdleFor(t) => choose!(
+                self,
+                format!("You've been idle for {}", d(t)),
+                format!("Du bist seit {} inaktiv", d(t)),
+                format!("Sin actividad desde hace {}", d(t))
+            ),
+            AwayQuestion(t) => choose!(
+                self,
+                format!("You were away for {}.  Were you working?", d(t)),
+                format!("Du warst {} weg.  Hast du gearbeitet?", d(t)),
+                format!("Estuviste fuera {}.  ¿Estabas trabajando?", d(t))
+            ),
+            Done => choose!(self, "Done", "Erledigt", "Hecho").to_string(),
+            Delay(t) => choose!(
+                self,
+                format!("Delay {}", d(t)),
+                format!("{} verschieben", d(t)),
+                format!("Posponer {}", d(t))
+            ),
+            StartRoutine => {
+                choose!(self, "Start routine", "Routine starten", "Empezar rutina").to_string()
+            }
+            SkipRoutine => choose!(
+                self,
+                "Skip routine",
+                "Routine überspringen",
+                "Saltar rutina"
+            )
+            .to_string(),
+            PersonalTime => {
+                choose!(self, "Personal time", "Freizeit", "Tiempo personal").to_string()
+            }
+            BackToWork => choose!(
+                self,
+                "Back to work",
+                "Zurück zur Arbeit",
+                "Volver al trabajo"
+            )
+            .to_string(),
+            Focus(t) => choose!(
+                self,
+                format!("Focus {}", d(t)),
+                format!("Fokus {}", d(t)),
+                format!("Concentrarse {}", d(t))
+            ),
+            EndFocus => {
+                choose!(self, "End focus", "Fokus beenden", "Terminar concentración").to_string()
+            }
+            FocusAnyway => choose!(
+                self,
+                "Focus past end of day",
+                "Über den Feierabend hinaus fokussieren",
+                "Concentrarse tras el fin de la jornada"
+            )
+            .to_string(),
+            Break => choose!(self, "Break", "Pause", "Descanso").to_string(),
+            Meeting => choose!(self, "Meeting", "Meeting", "Reunión").to_string(),
+            OfflineWork => choose!(
+                self,
+                "Offline work",
+                "Offline-Arbeit",
+                "Trabajo sin conexión"
+            )
+            .to_string(),
+            Quit => choose!(self, "Quit", "Beenden", "Salir").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(Language::German.duration(minutes(1)), "1 Minute");
+        assert_eq!(Language::German.duration(minutes(120)), "2 Stunden");
+        assert_eq!(Language::Spanish.duration(minutes(30)), "30 minutos");
+        assert_eq!(Language::Spanish.duration(minutes(60)), "1 hora");
+        assert_eq!(Language::Spanish.duration(minutes(62)), "1:02");
+        assert_eq!(
+            Language::German.duration(Duration::from_secs(20)),
+            "20 Sekunden"
+        );
+        assert_eq!(Language::German.duration(Duration::ZERO), "0 Minuten");
+        assert_eq!(
+            Language::German.spoken_duration(minutes(62)),
+            "1 Stunde und 2 Minuten"
+        );
+        assert_eq!(
+            Language::Spanish.spoken_duration(minutes(150)),
+            "2 horas y 30 minutos"
+        );
+        assert_eq!(
+            Language::English.spoken_duration(minutes(150)),
+            "two hours and thirty minutes"
+        );
+    }
+
+    #[test]
+    fn messages() {
+        let t = Duration::from_secs(2 * 60 * 60);
+        assert_eq!(
+            Language::English.text(Message::WorkingFor(t)),
+            "You've been working for 2 hours"
+        );
+        assert_eq!(
+            Language::German.text(Message::WorkingFor(t)),
+            "Du arbeitest seit 2 Stunden"
+        );
+        assert_eq!(
+            Language::Spanish.spoken(Message::Step("Sentadillas", Duration::from_secs(30))),
+            "Sentadillas durante 30 segundos"
+        );
+        assert_eq!(
+            Language::German.text(Message::PostponingSeeAbove("")),
+            "Verschiebe deine Pause, siehe oben."
+        );
+        assert_eq!(
+            Language::English.text(Message::OverWorkday(
+                Duration::from_secs(9 * 60 * 60 + 12 * 60),
+                Duration::from_secs(8 * 60 * 60)
+            )),
+            "You've worked 9:12 today (limit 8 hours)"
+        );
+        let config: toml::Value = toml::from_str("language = 'de'").unwrap();
+        assert_eq!(
+            config["language"].clone().try_into::<Language>().unwrap(),
+            Language::German
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! What the web servers we run on localhost have in common.
+
+use tiny_http::Request;
+
+/// Whether `request` was meant for us on `port`, rather than for some other
+/// site whose name has been pointed at localhost to read our answers.
+pub fn for_us(request: &Request, port: u16) -> bool {
+    request.headers().iter().any(|h| {
+        h.field.equiv("Host")
+            && [format!("127.0.0.1:{port}"), format!("localhost:{port}")]
+                .iter()
+                .any(|host| h.value.as_str().eq_ignore_ascii_case(host))
+    })
+}
+
+/// Turns away a request that isn't `for_us`.
+pub fn refuse(request: Request) {
+    request
+        .respond(tiny_http::Response::from_string("Forbidden").with_status_code(403))
+        .ok();
+}
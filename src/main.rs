@@ -3,8 +3,38 @@ use druid::{Data, Lens, TimerToken};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
+mod action;
+use action::Action;
+mod bar;
+mod dashboard;
+mod dbus;
 mod hours;
-use hours::Pretty;
+mod idle;
+use idle::IdleSource;
+mod history;
+use history::{Entry, Kind};
+mod cli;
+mod hooks;
+mod inbox;
+use inbox::Request;
+mod pomodoro;
+use pomodoro::Pomodoro;
+mod lang;
+use lang::{Language, Message};
+mod localhost;
+mod metrics;
+mod mqtt;
+mod notify;
+mod snapshot;
+use snapshot::Snapshot;
+mod sound;
+use sound::Cue;
+mod template;
+#[cfg(test)]
+mod testbus;
+mod tui;
+mod voice;
+mod webhook;
 
 use std::io::Write;
 use std::{
@@ -20,27 +50,109 @@ enum Status {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct Break {
+    #[serde(default)]
     prompt: String,
+    /// Things to prompt you to do instead of `prompt`, taking turns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    activities: Vec<String>,
+    #[serde(skip)]
+    last_activity: Option<String>,
     #[serde(with = "hours")]
     after: Duration,
+    /// A microbreak (like looking away from the screen) is only shown in the
+    /// window, finishes by itself once you've been idle for `MICROBREAK`, and
+    /// doesn't hold up other breaks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    micro: bool,
     #[serde(skip)]
     last_done: Duration,
+    /// How many times we've prompted for this today.
+    #[serde(skip)]
+    count: u32,
+    /// Steps to guide you through during the break.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    routine: Vec<Step>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Step {
+    text: String,
+    #[serde(with = "hours")]
+    time: Duration,
+}
+
+/// How long you need to be idle to finish a microbreak.
+const MICROBREAK: Duration = Duration::from_secs(20);
+/// How long to show a microbreak you are ignoring.
+const MICROBREAK_SHOWN: Duration = Duration::from_secs(2 * 60);
+
 impl Break {
     fn new<S: Into<String>>(prompt: S, after: Duration) -> Self {
         Break {
             prompt: prompt.into(),
             after,
+            micro: false,
             last_done: Duration::from_secs(0),
+            count: 0,
+            routine: Vec::new(),
+            activities: Vec::new(),
+            last_activity: None,
+        }
+    }
+    /// What to prompt you to do.  This is one of the `activities` if there
+    /// are any, favoring the ones you've done least recently according to
+    /// `history`, and never the same one twice in a row.
+    fn choose_prompt(&mut self, history: &[Entry], random: u64) -> String {
+        /// How many minutes ago we pretend you did something you never did.
+        const NEVER: i64 = 30 * 24 * 60;
+        if self.activities.is_empty() {
+            return self.prompt.clone();
         }
+        let now = chrono::Local::now();
+        let weights: Vec<u64> = self
+            .activities
+            .iter()
+            .map(|a| {
+                if self.activities.len() > 1 && self.last_activity.as_ref() == Some(a) {
+                    return 0;
+                }
+                let minutes_ago = history
+                    .iter()
+                    .filter(|e| e.kind == Kind::Done && &e.note == a)
+                    .map(|e| (now - e.start).num_minutes())
+                    .min()
+                    .unwrap_or(NEVER);
+                minutes_ago.clamp(0, NEVER) as u64 + 1
+            })
+            .collect();
+        let total: u64 = weights.iter().sum();
+        let mut r = random % total.max(1);
+        let mut chosen = &self.activities[0];
+        for (a, w) in self.activities.iter().zip(weights) {
+            if r < w {
+                chosen = a;
+                break;
+            }
+            r -= w;
+        }
+        self.last_activity = Some(chosen.clone());
+        chosen.clone()
     }
-    fn check(&mut self, worktime: Duration) -> bool {
+    /// What to call this break when it isn't being prompted for.
+    fn label(&self) -> String {
+        if self.prompt.is_empty() {
+            self.activities.join(" / ")
+        } else {
+            self.prompt.clone()
+        }
+    }
+    fn check(&self, worktime: Duration) -> bool {
         worktime > self.after + self.last_done
     }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     #[serde(with = "hours")]
     max_idle_time_while_working: Duration,
@@ -59,7 +171,43 @@ pub struct Config {
     when_to_emphasize_break: Duration,
     #[serde(with = "hours")]
     when_to_lock_screen: Duration,
+    /// What language to talk to you in: "en", "de" or "es".
+    language: Language,
+    /// Show prompts as desktop notifications, rather than by raising the
+    /// window (Linux, and other desktops that speak D-Bus).
+    notifications: bool,
+    /// Publish the `org.breaks.Breaks` service on the session bus.
+    dbus_service: bool,
+    /// Publish how things stand to an MQTT broker.
+    mqtt: Option<mqtt::Config>,
+    /// Serve Prometheus metrics at `http://127.0.0.1:<port>/metrics`.
+    metrics_port: Option<u16>,
+    /// Serve a dashboard web page at `http://127.0.0.1:<port>/`.
+    dashboard_port: Option<u16>,
+    /// Where to look for user activity, e.g. `["desktop", "terminals"]`.
+    idle_sources: Vec<IdleSource>,
+    /// When you come back after being away for longer than this, ask whether
+    /// you were in a meeting or working offline.
+    #[serde(with = "hours")]
+    ask_if_working_after: Option<Duration>,
+    /// How much personal screen time to allow in a day before reminding you.
+    #[serde(with = "hours")]
+    personal_limit: Option<Duration>,
+    /// How long the focus button holds off prompts.
+    #[serde(with = "hours")]
+    focus_time: Duration,
+    /// What to say at the end of the day, if not the default for your
+    /// language.  This, and the break prompts, may use variables like
+    /// `{worked}` (see the README).
+    end_of_day_prompt: Option<String>,
     breaks: Vec<Break>,
+    /// Take pomodoro breaks instead of the `breaks` above.
+    pomodoro: Option<pomodoro::Config>,
+    voice: voice::Config,
+    sounds: sound::Config,
+    hooks: hooks::Config,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    webhooks: Vec<webhook::Webhook>,
 }
 
 impl Default for Config {
@@ -86,17 +234,28 @@ impl Default for Config {
 
             when_to_emphasize_break: Duration::from_secs(60 * 2),
             when_to_lock_screen: Duration::from_secs(60 * 10),
+            language: Language::English,
+            notifications: false,
+            dbus_service: false,
+            mqtt: None,
+            metrics_port: None,
+            dashboard_port: None,
+            idle_sources: vec![IdleSource::Desktop],
+            ask_if_working_after: None,
+            personal_limit: None,
+            focus_time: Duration::from_secs(90 * 60),
+            end_of_day_prompt: None,
+            pomodoro: None,
+            voice: Default::default(),
+            sounds: Default::default(),
+            hooks: Default::default(),
+            webhooks: Vec::new(),
         }
     }
 }
 impl Config {
     fn config_path() -> std::path::PathBuf {
-        if let Some(h) = home::home_dir() {
-            std::fs::create_dir_all(h.join(".config/")).ok();
-            h.join(".config/breaks.toml")
-        } else {
-            "breaks.toml".into()
-        }
+        config_path("breaks.toml")
     }
     fn is_new_day(&self, t: Duration) -> bool {
         if self.day_resets_after.as_secs() == 0 {
@@ -107,11 +266,32 @@ impl Config {
             t > self.day_resets_after
         }
     }
+    /// Checks for problems that parsing can't catch.
+    fn validate(&self) -> anyhow::Result<()> {
+        if let Some(p) = &self.end_of_day_prompt {
+            template::check(p)?;
+        }
+        self.sounds.check()?;
+        for w in self.webhooks.iter() {
+            w.check()?;
+        }
+        for b in self.breaks.iter() {
+            template::check(&b.prompt)?;
+            for a in b.activities.iter() {
+                template::check(a)?;
+            }
+        }
+        Ok(())
+    }
     fn load() -> anyhow::Result<Self> {
         if let Ok(contents) = std::fs::read_to_string(Self::config_path()) {
             // If file is readable but not parsable, we want to die with a nice error.
-            toml::de::from_str(&contents)
-                .context(format!("Unable to parse {:?}", Self::config_path()))
+            let config: Config = toml::de::from_str(&contents)
+                .context(format!("Unable to parse {:?}", Self::config_path()))?;
+            config
+                .validate()
+                .context(format!("Problem in {:?}", Self::config_path()))?;
+            Ok(config)
         } else {
             let c = Default::default();
             std::fs::write(
@@ -124,24 +304,148 @@ impl Config {
     }
 }
 
+/// Where to keep the file `name` among your settings, in `~/.config`.
+fn config_path(name: &str) -> std::path::PathBuf {
+    if let Some(h) = home::home_dir() {
+        std::fs::create_dir_all(h.join(".config/")).ok();
+        h.join(".config").join(name)
+    } else {
+        name.into()
+    }
+}
+
+/// Something to prompt you with.
+#[derive(Clone, Debug)]
+struct Prompt {
+    /// What it's for, as in `State::prompt_names`.
+    names: Vec<String>,
+    text: String,
+    /// How to say `text`.
+    spoken: String,
+    routine: Vec<Step>,
+    /// The activities it asks for, as written in the config, to note in
+    /// the history once they're done.
+    activities: Vec<String>,
+}
+
+impl Prompt {
+    fn new(name: &str, text: String, spoken: String) -> Self {
+        Prompt {
+            names: vec![name.to_string()],
+            text,
+            spoken,
+            routine: Vec::new(),
+            activities: Vec::new(),
+        }
+    }
+}
+
+/// How a frontend should get your attention after a tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Attention {
+    /// Show the prompt.
+    show: bool,
+    /// Insist on it, as it's been ignored for a while.
+    insist: bool,
+}
+
+#[derive(Clone, Data)]
+struct Microbreak {
+    prompt: String,
+    since: Instant,
+    /// How much longer you need to look away.
+    left: Duration,
+}
+
+/// A break routine that you are going through.
+#[derive(Clone, Data)]
+struct Routine {
+    steps: Arc<Vec<Step>>,
+    /// The step you are on, and when you started it.
+    step: Option<(usize, Instant)>,
+    /// How long until the next step.
+    left: Duration,
+}
+
 #[derive(Clone, Data, Lens)]
 struct State {
     #[data(ignore)]
     tts: Option<Arc<Mutex<tts::Tts>>>,
     #[data(ignore)]
+    player: sound::Player,
+    #[data(ignore)]
+    notifier: Option<notify::Notifier>,
+    /// The prompt we're showing as a notification, and whether it offers
+    /// to be done.
+    #[data(ignore)]
+    notified: Option<(String, bool)>,
+    /// The microbreak we're showing as a notification.
+    #[data(ignore)]
+    notified_microbreak: Option<String>,
+    #[data(ignore)]
+    server: Option<dbus::Server>,
+    #[data(ignore)]
+    webhooks: Option<webhook::Sender>,
+    #[data(ignore)]
+    mqtt: Option<mqtt::Publisher>,
+    #[data(ignore)]
+    metrics: metrics::Metrics,
+    #[data(ignore)]
+    dashboard: Option<dashboard::Dashboard>,
+    #[data(ignore)]
     config: Config,
+    #[data(ignore)]
+    history_path: std::path::PathBuf,
+    /// Where we write the status file for the command line.
+    #[data(ignore)]
+    status_path: std::path::PathBuf,
 
     am_prompting: Option<String>,
+    microbreak: Option<Microbreak>,
+    /// The routine for the break we are prompting, until you finish or skip it.
+    routine: Option<Routine>,
+    /// No prompts until this time.
+    focus_until: Option<Instant>,
+    /// Focus time we want you to confirm, because it goes past the end of the day.
+    confirm_focus: Option<Duration>,
+    /// Prompts that came due while focusing.
+    #[data(ignore)]
+    queued: Vec<Prompt>,
+    /// What we're prompting for, like the break or "end of day", for metrics.
+    /// There can be several once focusing is over.
+    #[data(ignore)]
+    prompt_names: Vec<String>,
+    /// How to say what we're prompting, which may differ from how it's written.
+    #[data(ignore)]
+    spoken_prompt: String,
+    /// The activities we're prompting for, as in `Prompt::activities`.
+    #[data(ignore)]
+    prompt_activities: Vec<String>,
+    /// Time away from the computer that we are asking about.
+    away: Option<(Instant, Duration)>,
     status_report: String,
     latest_update: String,
+    pomodoro_report: String,
 
     #[data(ignore)]
     status: Status,
     #[data(ignore)]
     breaks: Vec<Break>,
+    #[data(ignore)]
+    pomodoro: Pomodoro,
+    /// How many times we've told you it's the end of the day.
+    #[data(ignore)]
+    end_of_day_count: u32,
     screen_time: Duration,
+    /// Whether the computer is being used for something other than work.
+    personal: bool,
+    personal_time: Duration,
 
     last_prompt: Instant,
+    /// Since when the prompt has been showing without you doing anything
+    /// about it, for the lock hook.
+    #[data(ignore)]
+    ignored_since: Option<Instant>,
 }
 
 impl Default for State {
@@ -159,31 +463,432 @@ impl State {
 impl State {
     fn new(config: Config) -> State {
         State {
-            tts: tts::Tts::default()
-                .ok()
+            tts: config
+                .voice
+                .speaker(config.language)
                 .map(|tts| Arc::new(Mutex::new(tts))),
+            player: sound::Player::new(config.sounds.clone()),
+            notifier: None,
+            notified: None,
+            notified_microbreak: None,
+            server: None,
+            mqtt: None,
+            metrics: Default::default(),
+            dashboard: None,
+            webhooks: (!config.webhooks.is_empty())
+                .then(|| webhook::Sender::start(config.webhooks.clone(), config.language)),
             status: Status::WorkingSince(Instant::now()),
             screen_time: Duration::from_secs(0),
+            personal: false,
+            personal_time: Duration::from_secs(0),
             last_prompt: Instant::now(),
+            ignored_since: None,
             breaks: config.breaks.clone(),
             am_prompting: None,
+            microbreak: None,
+            routine: None,
+            focus_until: None,
+            confirm_focus: None,
+            queued: Vec::new(),
+            prompt_names: Vec::new(),
+            spoken_prompt: "".to_string(),
+            prompt_activities: Vec::new(),
+            away: None,
             status_report: "".to_string(),
             latest_update: "".to_string(),
+            pomodoro: Pomodoro::default(),
+            end_of_day_count: 0,
+            pomodoro_report: "".to_string(),
             config,
+            history_path: history::history_path(),
+            status_path: snapshot::status_path(),
         }
     }
+    fn text(&self, m: Message) -> String {
+        self.config.language.text(m)
+    }
     fn say(&self, msg: &str) {
-        self.tts
-            .as_ref()
-            .map(|tts| tts.lock().unwrap().speak(msg, false));
+        self.alert(None, msg);
     }
-    fn prompt(&mut self, msg: String) {
-        self.say(msg.as_str());
-        self.am_prompting = Some(msg);
+    /// Plays the sound for `cue`, if there is one, and then says `msg`
+    /// unless we're only to play the sound.
+    fn alert(&self, cue: Option<Cue>, msg: &str) {
+        if self.focusing() || self.config.voice.is_quiet(chrono::Local::now().time()) {
+            return;
+        }
+        let tts = self.tts.clone();
+        let msg = msg.to_string();
+        let speak = move || {
+            if let (Some(tts), false) = (tts, msg.is_empty()) {
+                tts.lock().unwrap().speak(msg, false).ok();
+            }
+        };
+        match cue {
+            Some(cue) => self.player.play(cue, speak),
+            None => speak(),
+        }
     }
-    fn announce(&self) {
-        if let Some(p) = self.am_prompting.as_ref() {
-            self.say(p.as_str());
+    /// Prompts you after the sound for `cue`, and offers you the prompt's
+    /// routine if there is one.
+    fn prompt(&mut self, cue: Cue, prompt: Prompt) {
+        if self.focusing() {
+            self.queued.push(prompt);
+            return;
+        }
+        for name in &prompt.names {
+            self.metrics.count(name, metrics::Outcome::Issued);
+        }
+        self.alert(Some(cue), &prompt.spoken);
+        if let Some(Err(e)) = self.server.as_ref().map(|s| s.prompted(&prompt.text)) {
+            eprintln!("Unable to signal the prompt: {e}");
+        }
+        match cue {
+            // Only the first time you go past the end of the day, not each
+            // time we remind you.
+            Cue::EndOfDay if self.end_of_day_count > 1 => (),
+            Cue::EndOfDay => self.hook(hooks::Event::EndOfDay, Some(&prompt.text), None),
+            _ => self.hook(hooks::Event::Prompt, Some(&prompt.text), None),
+        }
+        self.routine = (!prompt.routine.is_empty()).then(|| Routine {
+            steps: Arc::new(prompt.routine),
+            step: None,
+            left: Duration::from_secs(0),
+        });
+        self.prompt_names = prompt.names;
+        self.prompt_activities = prompt.activities;
+        self.am_prompting = Some(prompt.text);
+        self.spoken_prompt = prompt.spoken;
+    }
+    /// Runs your hook for `event`, if you have one, and sends it to your
+    /// webhooks.
+    fn hook(&self, event: hooks::Event, prompt: Option<&str>, away: Option<Duration>) {
+        let payload = hooks::Payload {
+            event,
+            worked: self.worktime().as_secs(),
+            workday: self.config.workday.as_secs(),
+            prompt: prompt.map(str::to_string),
+            away: away.map(|t| t.as_secs()),
+        };
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.send(&payload);
+        }
+        self.config.hooks.fire(payload);
+    }
+    /// Reminds you of the prompt, more insistently if we're to `emphasize` it.
+    fn announce(&self, emphasize: bool) {
+        if self.am_prompting.is_some() {
+            let cue = if emphasize {
+                Cue::Emphasize
+            } else {
+                Cue::Prompt
+            };
+            if emphasize {
+                self.count_prompt(metrics::Outcome::Escalated);
+            }
+            self.alert(Some(cue), self.spoken_prompt.as_str());
+        }
+    }
+    /// Counts `outcome` for each thing we're prompting for.
+    fn count_prompt(&self, outcome: metrics::Outcome) {
+        for name in &self.prompt_names {
+            self.metrics.count(name, outcome);
+        }
+    }
+    fn record(&self, entry: Entry) {
+        if entry.kind == Kind::Work {
+            self.metrics.session(entry.duration);
+        }
+        if let Err(e) = history::append(&self.history_path, &entry) {
+            eprintln!("Unable to save history: {e}");
+        }
+    }
+    /// Accounts for the time away that we asked about.
+    fn spent_away(&mut self, kind: Kind) {
+        if let Some(away) = self.count_away(kind) {
+            self.status_report = self.text(match kind {
+                Kind::Meeting => Message::CountingMeeting(away),
+                Kind::OfflineWork => Message::CountingOfflineWork(away),
+                _ => Message::GladOfBreak(away),
+            });
+        }
+    }
+    /// Counts the time away that we asked about as `kind`, without saying
+    /// so, and returns how long it was.
+    fn count_away(&mut self, kind: Kind) -> Option<Duration> {
+        let (start, away) = self.away.take()?;
+        if kind != Kind::Break {
+            self.screen_time += away;
+        }
+        self.record(Entry::new(kind, start, away));
+        Some(away)
+    }
+    fn handle(&mut self, request: Request) {
+        match request {
+            Request::Add { duration, reason } => {
+                // The command line refuses more, but the inbox is just a file.
+                let duration = duration.min(inbox::MOST_AT_ONCE);
+                self.screen_time = self.screen_time.saturating_add(duration);
+                self.status_report = self.text(Message::Added(duration));
+                self.record(Entry::new(Kind::Added, Instant::now(), duration).noted(reason));
+            }
+            Request::Subtract { duration, reason } => {
+                let mut subtracted = duration.min(self.screen_time);
+                self.screen_time -= subtracted;
+                if let (Status::WorkingSince(start), false) = (&mut self.status, self.personal) {
+                    // Whatever we couldn't take from earlier work comes out of this session.
+                    let later = (duration - subtracted).min(start.elapsed());
+                    *start += later;
+                    subtracted += later;
+                }
+                // There may not have been that much work to take it from.
+                self.status_report = self.text(Message::Subtracted(subtracted));
+                self.record(Entry::new(Kind::Subtracted, Instant::now(), subtracted).noted(reason));
+            }
+            Request::Personal { on } => self.set_personal(on.unwrap_or(!self.personal)),
+        }
+    }
+    /// Starts or stops counting time at the computer as personal rather than work.
+    fn set_personal(&mut self, on: bool) {
+        if on == self.personal {
+            return;
+        }
+        self.split_session();
+        self.personal = on;
+        if on {
+            self.am_prompting = None;
+            self.status_report = self.text(Message::PersonalOn);
+        } else {
+            self.status_report = self.text(Message::PersonalOff);
+        }
+    }
+    /// Counts the time at the computer so far, and starts a new session.
+    fn split_session(&mut self) {
+        if let Status::WorkingSince(start) = self.status {
+            // As when you go idle, the session ends when you last did
+            // anything.
+            let idle = idle::idle_time(&self.config.idle_sources).unwrap_or_else(|e| {
+                eprintln!("Unable to check idle time: {e}");
+                Duration::from_secs(0)
+            });
+            let end = (Instant::now() - idle).max(start);
+            let so_far = end.duration_since(start);
+            if self.personal {
+                self.personal_time += so_far;
+                self.record(Entry::new(Kind::Personal, start, so_far));
+            } else {
+                self.screen_time += so_far;
+                self.record(Entry::new(Kind::Work, start, so_far));
+            }
+            self.status = Status::WorkingSince(end);
+        }
+    }
+    /// Forgets about the day so far.
+    fn reset_day(&mut self) {
+        self.hook(hooks::Event::NewDay, None, None);
+        // Yesterday's absence can't count towards today.
+        self.count_away(Kind::Break);
+        self.screen_time = Duration::from_secs(0);
+        self.personal_time = Duration::from_secs(0);
+        self.personal = false;
+        self.pomodoro = Pomodoro::default();
+        self.end_of_day_count = 0;
+        for b in self.breaks.iter_mut() {
+            b.last_done = Duration::from_secs(0);
+            b.count = 0;
+        }
+    }
+    /// Does what was asked from outside the window, just as if you'd
+    /// pressed the button.  Returns whether to show the window.
+    fn act(&mut self, action: Action) -> bool {
+        match action {
+            Action::Done => return self.done(),
+            Action::Delay(time) => self.delay(time),
+            Action::Focus(time) => self.focus(time, false),
+            Action::ResetDay => {
+                self.split_session();
+                self.reset_day();
+                self.am_prompting = None;
+                self.status_report = self.text(Message::DayReset);
+            }
+        }
+        false
+    }
+    /// How things stand.
+    fn snapshot(&self) -> Snapshot {
+        let worked = self.worktime();
+        let mut breaks: Vec<_> = self
+            .breaks
+            .iter()
+            .filter(|b| !b.micro && self.config.pomodoro.is_none())
+            .map(|b| snapshot::NextBreak {
+                name: b.label(),
+                due_in: (b.after + b.last_done).saturating_sub(worked),
+            })
+            .collect();
+        breaks.sort_by_key(|b| b.due_in);
+        let next_break = breaks
+            .first()
+            .filter(|_| self.am_prompting.is_none() && worked < self.config.workday)
+            .cloned();
+        let (idle, since) = match self.status {
+            Status::WorkingSince(t) => (false, t),
+            Status::IdleSince(t) => (true, t),
+        };
+        Snapshot {
+            worked,
+            remaining: self.config.workday.saturating_sub(worked),
+            workday: self.config.workday,
+            idle,
+            since: history::local_time(since),
+            prompt: self.am_prompting.clone(),
+            next_break,
+            breaks,
+            language: self.config.language,
+            updated: chrono::Local::now(),
+        }
+    }
+    /// Lets the world outside the window know how things stand.
+    fn publish(&self) {
+        let snapshot = self.snapshot();
+        if let Err(e) = snapshot.write(&self.status_path) {
+            eprintln!("Unable to write {:?}: {e}", self.status_path);
+        }
+        if let Some(Err(e)) = self.server.as_ref().map(|s| s.update(&snapshot)) {
+            eprintln!("Unable to update the D-Bus service: {e}");
+        }
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.update(&snapshot);
+        }
+        self.metrics.update(&snapshot);
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.update(&snapshot);
+        }
+    }
+    /// Time worked today, including the current session.
+    fn worktime(&self) -> Duration {
+        match self.status {
+            Status::WorkingSince(start) if !self.personal => {
+                self.screen_time + Instant::now().duration_since(start)
+            }
+            _ => self.screen_time,
+        }
+    }
+    fn focusing(&self) -> bool {
+        self.focus_until.is_some_and(|until| Instant::now() < until)
+    }
+    /// Holds off all prompts for `time`.  Unless `past_end_of_day`, refuses to
+    /// focus beyond the end of the workday and asks for confirmation instead.
+    fn focus(&mut self, time: Duration, past_end_of_day: bool) {
+        let time = time.min(LONGEST_WAIT);
+        if !past_end_of_day && self.worktime() + time > self.config.workday {
+            self.confirm_focus = Some(time);
+            self.status_report = self.text(Message::FocusPastEndOfDay(time));
+            return;
+        }
+        self.confirm_focus = None;
+        self.focus_until = Instant::now().checked_add(time);
+        if let Some(text) = self.am_prompting.take() {
+            self.queued.push(Prompt {
+                names: std::mem::take(&mut self.prompt_names),
+                text,
+                spoken: std::mem::take(&mut self.spoken_prompt),
+                routine: self
+                    .routine
+                    .take()
+                    .map_or_else(Vec::new, |r| r.steps.to_vec()),
+                activities: std::mem::take(&mut self.prompt_activities),
+            });
+        }
+        self.status_report = self.text(Message::Focusing(time));
+    }
+    /// Stops focusing, and delivers any prompts we held off.
+    fn end_focus(&mut self) {
+        self.focus_until = None;
+        self.status_report = self.text(Message::FocusOver);
+        if !self.queued.is_empty() {
+            let queued = std::mem::take(&mut self.queued);
+            let join = |pick: fn(&Prompt) -> &str, with| {
+                queued.iter().map(pick).collect::<Vec<_>>().join(with)
+            };
+            let prompt = Prompt {
+                names: queued.iter().flat_map(|q| q.names.clone()).collect(),
+                text: join(|q| &q.text, ".  "),
+                spoken: join(|q| &q.spoken, ".  "),
+                routine: queued.iter().flat_map(|q| q.routine.clone()).collect(),
+                activities: queued.iter().flat_map(|q| q.activities.clone()).collect(),
+            };
+            self.prompt(Cue::Prompt, prompt);
+        }
+    }
+    /// Whether you can say you've done what we prompted.
+    fn can_be_done(&self) -> bool {
+        // A routine needs to be finished or skipped first.
+        self.am_prompting.is_some() && self.routine.is_none()
+    }
+    /// You say you've done what we prompted.
+    fn done(&mut self) -> bool {
+        if let Some(prompt) = self.am_prompting.take_if(|_| self.routine.is_none()) {
+            self.count_prompt(metrics::Outcome::Acknowledged);
+            self.status_report = self.text(Message::WellDone(&prompt));
+            // Activities are noted as written, for choosing between them.
+            let notes = if self.prompt_activities.is_empty() {
+                vec![prompt]
+            } else {
+                std::mem::take(&mut self.prompt_activities)
+            };
+            for note in notes {
+                self.record(
+                    Entry::new(Kind::Done, Instant::now(), Duration::from_secs(0)).noted(note),
+                );
+            }
+            true
+        } else {
+            false
+        }
+    }
+    /// Puts off the current prompt for `time`, or `LONGEST_WAIT` at most.
+    fn delay(&mut self, time: Duration) {
+        let Some(until) = Instant::now().checked_add(time.min(LONGEST_WAIT)) else {
+            return;
+        };
+        if let Some(prompt) = &self.am_prompting {
+            self.count_prompt(metrics::Outcome::Snoozed);
+            self.last_prompt = until;
+            self.status_report = self.text(Message::PuttingOff(prompt));
+        }
+    }
+    /// Shows the prompt as a notification, unless it's been put off or
+    /// you're doing its routine, and takes the notification down otherwise.
+    /// Likewise for the microbreak and its countdown.
+    fn update_notification(&mut self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        let prompt = self
+            .am_prompting
+            .clone()
+            .filter(|_| self.after_last_prompt() && !self.doing_routine())
+            .map(|p| (p, self.can_be_done()));
+        if prompt != self.notified {
+            let result = match &prompt {
+                Some((p, can_be_done)) => notifier.show(p, *can_be_done, self.config.language),
+                None => notifier.close(),
+            };
+            if let Err(e) = result {
+                eprintln!("Unable to update the notification: {e}");
+            }
+            self.notified = prompt;
+        }
+        let microbreak = self.microbreak.as_ref().map(|_| self.microbreak_report());
+        if microbreak != self.notified_microbreak {
+            let result = match &microbreak {
+                Some(m) => notifier.show_microbreak(m),
+                None => notifier.close_microbreak(),
+            };
+            if let Err(e) = result {
+                eprintln!("Unable to update the microbreak notification: {e}");
+            }
+            self.notified_microbreak = microbreak;
         }
     }
     fn after_last_prompt(&self) -> bool {
@@ -192,91 +897,412 @@ impl State {
     fn since_last_prompt(&self) -> Duration {
         Instant::now().saturating_duration_since(self.last_prompt)
     }
+    fn update_pomodoro(&mut self, config: &pomodoro::Config, idle: Duration) {
+        use pomodoro::Event;
+        let now = Instant::now();
+        let language = self.config.language;
+        let prompt = |long| Message::PomodoroBreak {
+            long,
+            time: config.break_time(long),
+        };
+        match self.pomodoro.update(config, now, idle) {
+            Some(Event::Started(n)) => self.status_report = self.text(Message::PomodoroStarting(n)),
+            Some(Event::BreakDue(long)) => {
+                self.prompt(
+                    Cue::Prompt,
+                    Prompt::new(
+                        "pomodoro",
+                        language.text(prompt(long)),
+                        language.spoken(prompt(long)),
+                    ),
+                );
+                self.last_prompt = now;
+            }
+            Some(Event::Rested(long)) => {
+                if self.am_prompting == Some(language.text(prompt(long))) {
+                    self.am_prompting = None;
+                }
+                self.status_report = self.text(Message::PomodoroRested);
+                self.alert(Some(Cue::BreakComplete), "");
+            }
+            Some(Event::Skipped(long)) => {
+                if self.am_prompting == Some(language.text(prompt(long))) {
+                    self.am_prompting = None;
+                }
+                self.status_report = self.text(Message::PomodoroSkipped);
+            }
+            None => (),
+        }
+        self.pomodoro_report = self.pomodoro.describe(config, now, language);
+    }
+    /// Counts down the current microbreak, which is done once you've been
+    /// idle for long enough.
+    fn update_microbreak(&mut self) -> anyhow::Result<()> {
+        if let Some(m) = &mut self.microbreak {
+            let t = idle::idle_time(&self.config.idle_sources)?;
+            if t >= MICROBREAK {
+                self.microbreak = None;
+                self.status_report = self.text(Message::NiceMicrobreak);
+                self.alert(Some(Cue::BreakComplete), "");
+            } else if m.since.elapsed() > MICROBREAK_SHOWN {
+                self.microbreak = None;
+            } else {
+                m.left = MICROBREAK - t;
+            }
+        }
+        Ok(())
+    }
+    fn doing_routine(&self) -> bool {
+        self.routine.as_ref().is_some_and(|r| r.step.is_some())
+    }
+    fn start_routine(&mut self) {
+        if let Some(r) = &mut self.routine {
+            r.step = Some((0, Instant::now()));
+            r.left = r.steps[0].time;
+            let step = &r.steps[0];
+            let msg = self
+                .config
+                .language
+                .spoken(Message::Step(&step.text, step.time));
+            self.say(&msg);
+        }
+    }
+    /// The step of the routine you're on, or what it involves.
+    fn routine_report(&self) -> String {
+        match &self.routine {
+            Some(Routine {
+                steps,
+                step: Some((i, _)),
+                left,
+            }) => self.text(Message::RoutineStep {
+                n: i + 1,
+                of: steps.len(),
+                text: &steps[*i].text,
+                left: *left,
+            }),
+            Some(Routine { steps, .. }) => self.text(Message::RoutineSummary {
+                steps: steps.len(),
+                time: steps.iter().map(|s| s.time).sum(),
+            }),
+            None => "".to_string(),
+        }
+    }
+    fn microbreak_report(&self) -> String {
+        if let Some(m) = &self.microbreak {
+            format!("{} ({})", m.prompt, self.config.language.duration(m.left))
+        } else {
+            "".to_string()
+        }
+    }
+    fn skip_routine(&mut self) {
+        self.routine = None;
+        self.status_report = self.text(Message::RoutineSkipped);
+    }
+    /// Moves on to the next step of the routine when it's time.
+    fn update_routine(&mut self) {
+        if self.am_prompting.is_none() {
+            self.routine = None;
+        }
+        let Some(Routine {
+            steps,
+            step: Some((i, since)),
+            left,
+        }) = &mut self.routine
+        else {
+            return;
+        };
+        let elapsed = since.elapsed();
+        if elapsed < steps[*i].time {
+            *left = steps[*i].time - elapsed;
+        } else if *i + 1 < steps.len() {
+            *i += 1;
+            *since = Instant::now();
+            *left = steps[*i].time;
+            let msg = self
+                .config
+                .language
+                .spoken(Message::Step(&steps[*i].text, steps[*i].time));
+            self.say(&msg);
+        } else {
+            self.routine = None;
+            self.status_report = self.text(Message::RoutineFinished);
+            self.alert(
+                Some(Cue::BreakComplete),
+                &self.config.language.spoken(Message::RoutineFinished),
+            );
+        }
+    }
+    /// Checks on you if `check_in`, or else just counts down microbreaks and
+    /// routines, and lets the world know.  Every frontend calls this every
+    /// `tick_every()`, and checks in every `UPDATE_EVERY`.  If we can't tell
+    /// whether you're idle, as over ssh, we try again next time.
+    fn tick(&mut self, check_in: bool) -> Attention {
+        let mut attention = Attention::default();
+        if check_in {
+            if let Err(e) = self.update() {
+                eprintln!("Unable to check on you: {e}");
+            }
+            if self.am_prompting.is_some() && self.after_last_prompt() && !self.doing_routine() {
+                attention.show = true;
+                attention.insist = self.since_last_prompt() > self.config.when_to_emphasize_break;
+                if attention.insist {
+                    self.last_prompt = Instant::now();
+                }
+                self.announce(attention.insist);
+                if self.notifier.as_ref().is_some_and(|n| !n.showing()) {
+                    // You dismissed it without answering, so bring it back.
+                    self.notified = None;
+                }
+                let ignored_since = *self.ignored_since.get_or_insert(Instant::now());
+                if ignored_since.elapsed() > self.config.when_to_lock_screen {
+                    self.hook(hooks::Event::Lock, self.am_prompting.as_deref(), None);
+                    self.ignored_since = Some(Instant::now());
+                }
+            } else {
+                self.ignored_since = None;
+            }
+        }
+        if let Err(e) = self.update_microbreak() {
+            eprintln!("Unable to check on microbreak: {e}");
+        }
+        self.update_routine();
+        self.update_notification();
+        self.publish();
+        attention
+    }
+    /// How long until the next tick.
+    fn tick_every(&self) -> Duration {
+        // Count down microbreaks and routines by the second.
+        if self.microbreak.is_some() || self.doing_routine() {
+            Duration::from_secs(1)
+        } else {
+            UPDATE_EVERY
+        }
+    }
     fn update(&mut self) -> anyhow::Result<()> {
         use Status::*;
+        if self.focus_until.is_some() && !self.focusing() {
+            self.end_focus();
+        }
+        match inbox::receive() {
+            Ok(requests) => requests.into_iter().for_each(|r| self.handle(r)),
+            Err(e) => eprintln!("Unable to read requests: {e}"),
+        }
+        let t = idle::idle_time(&self.config.idle_sources)?;
+        if let (Some(pomodoro), false) = (self.config.pomodoro.clone(), self.personal) {
+            self.update_pomodoro(&pomodoro, t);
+        }
         let config = &self.config;
-        let t = idle_time()?;
         let now = Instant::now();
         match self.status {
+            WorkingSince(start) if self.personal => {
+                let start_idle = now - t;
+                if t > config.max_idle_time_while_working {
+                    self.personal_time += start_idle.duration_since(start);
+                    self.record(Entry::new(
+                        Kind::Personal,
+                        start,
+                        start_idle.duration_since(start),
+                    ));
+                    self.status = IdleSince(start_idle);
+                    self.status_report = self.text(Message::PersonalAfk(self.personal_time));
+                } else {
+                    let personal = start_idle.duration_since(start) + self.personal_time;
+                    // This comes up again after focusing, so needn't be queued.
+                    if config.personal_limit.is_some_and(|limit| personal > limit)
+                        && self.since_last_prompt() > config.just_started
+                        && !self.focusing()
+                    {
+                        let language = self.config.language;
+                        self.prompt(
+                            Cue::Prompt,
+                            Prompt::new(
+                                "personal time",
+                                language.text(Message::PersonalScreenTime(personal)),
+                                language.spoken(Message::PersonalScreenTime(personal)),
+                            ),
+                        );
+                        self.last_prompt = now;
+                    }
+                    self.latest_update = self.text(Message::PersonalSoFar {
+                        personal,
+                        worked: self.screen_time,
+                    });
+                }
+            }
             WorkingSince(start) => {
                 if t > config.max_idle_time_while_working && !am_in_meet() {
                     let start_idle = now - t;
                     self.screen_time += start_idle.duration_since(start);
+                    self.record(Entry::new(
+                        Kind::Work,
+                        start,
+                        start_idle.duration_since(start),
+                    ));
                     self.status = IdleSince(start_idle);
-                    self.status_report = format!(
-                        "After working {} you are now AFK!",
-                        self.screen_time.pretty()
-                    );
+                    self.status_report = self.text(Message::WorkAfk(self.screen_time));
                 } else {
                     let this_work = (now - t).duration_since(start);
+                    let worked = this_work + self.screen_time;
+                    if self.breaks.iter().any(|b| b.micro && b.check(worked)) {
+                        // Microbreaks that come due while we can't show them are skipped.
+                        let show = self.microbreak.is_none() && !self.focusing() && !am_in_meet();
+                        for b in self
+                            .breaks
+                            .iter_mut()
+                            .filter(|b| b.micro && b.check(worked))
+                        {
+                            b.last_done = worked;
+                            if show {
+                                b.count += 1;
+                                let values = template::Values::now(
+                                    worked,
+                                    config.workday,
+                                    this_work,
+                                    b.count,
+                                    config.language,
+                                );
+                                self.microbreak = Some(Microbreak {
+                                    prompt: template::render(
+                                        &b.choose_prompt(&[], random()),
+                                        &values,
+                                    ),
+                                    since: now,
+                                    left: MICROBREAK,
+                                });
+                            }
+                        }
+                    }
                     if this_work + self.screen_time > config.workday
                         && self.since_last_prompt() > config.just_started
                         && self.since_last_prompt() > 2 * config.max_idle_time_while_working
                         && this_work > config.just_started
                         && this_work > 2 * config.max_idle_time_while_working
+                        && !self.focusing()
                     {
-                        self.prompt(format!(
-                            "End of day after {}",
-                            (this_work + self.screen_time).pretty()
-                        ));
+                        self.end_of_day_count += 1;
+                        let worked = this_work + self.screen_time;
+                        let values = template::Values::now(
+                            worked,
+                            config.workday,
+                            this_work,
+                            self.end_of_day_count,
+                            config.language,
+                        );
+                        let template = config
+                            .end_of_day_prompt
+                            .as_deref()
+                            .unwrap_or(config.language.end_of_day_prompt());
+                        self.prompt(
+                            Cue::EndOfDay,
+                            Prompt::new(
+                                "end of day",
+                                template::render(template, &values),
+                                template::render_spoken(template, &values),
+                            ),
+                        );
                         self.last_prompt = now;
                     } else if (this_work < config.just_started
                         || this_work > config.good_chunk_of_work)
                         && self.am_prompting.is_none()
                         && !am_in_meet()
+                        && config.pomodoro.is_none()
                         && this_work + self.screen_time < config.workday
                     // Stop giving reminders at end of day.
                     {
                         let mut prompt = None;
-                        for b in self.breaks.iter_mut() {
+                        let mut history = None;
+                        for b in self.breaks.iter_mut().filter(|b| !b.micro) {
                             if b.check(this_work + self.screen_time) {
                                 let prompt_gap = now.duration_since(self.last_prompt);
                                 if self.am_prompting.is_some() {
-                                    self.status_report =
-                                        format!("Postponing {}, see above.", b.prompt);
+                                    self.status_report = config
+                                        .language
+                                        .text(Message::PostponingSeeAbove(&b.label()));
                                 } else if am_in_meet() {
-                                    self.status_report =
-                                        format!("Postponing {} while you meet.", b.prompt);
+                                    self.status_report = config
+                                        .language
+                                        .text(Message::PostponingWhileMeeting(&b.label()));
                                 } else if prompt_gap < self.config.minimum_time_between_breaks {
-                                    self.status_report = format!(
-                                        "Postponing {} for {}.",
-                                        b.prompt,
-                                        (config.minimum_time_between_breaks - prompt_gap).pretty()
-                                    );
+                                    self.status_report =
+                                        config.language.text(Message::PostponingFor(
+                                            &b.label(),
+                                            config.minimum_time_between_breaks - prompt_gap,
+                                        ));
                                 } else {
-                                    prompt = Some(b.prompt.clone());
+                                    let history = history.get_or_insert_with(|| {
+                                        history::read(&self.history_path).unwrap_or_else(|e| {
+                                            eprintln!("Unable to read history: {e}");
+                                            Vec::new()
+                                        })
+                                    });
+                                    b.count += 1;
+                                    let worked = this_work + self.screen_time;
+                                    let values = template::Values::now(
+                                        worked,
+                                        config.workday,
+                                        this_work,
+                                        b.count,
+                                        config.language,
+                                    );
+                                    let chosen = b.choose_prompt(history, random());
+                                    prompt = Some(Prompt {
+                                        routine: b.routine.clone(),
+                                        activities: if b.activities.is_empty() {
+                                            Vec::new()
+                                        } else {
+                                            vec![chosen.clone()]
+                                        },
+                                        ..Prompt::new(
+                                            &b.label(),
+                                            template::render(&chosen, &values),
+                                            template::render_spoken(&chosen, &values),
+                                        )
+                                    });
                                     self.last_prompt = Instant::now();
                                     b.last_done = this_work + self.screen_time;
                                 }
                             }
                         }
-                        if let Some(p) = prompt {
-                            self.prompt(p);
+                        if let Some(prompt) = prompt {
+                            self.prompt(Cue::Prompt, prompt);
                         }
                     }
-                    self.latest_update = format!(
-                        "You've been working for {}",
-                        (this_work + self.screen_time).pretty()
-                    );
+                    self.latest_update =
+                        self.text(Message::WorkingFor(this_work + self.screen_time));
                     std::io::stdout().flush()?;
                 }
             }
             IdleSince(start) => {
                 let start_idle = now - t;
-                if start_idle.duration_since(start) > config.max_idle_time_while_working {
+                let away = start_idle.duration_since(start);
+                if away > config.max_idle_time_while_working {
                     self.status = WorkingSince(start_idle);
-                    self.status_report = format!(
-                        "You resumed working after a {} break.",
-                        start_idle.duration_since(start).pretty()
-                    );
-                } else if config.is_new_day(t) && self.screen_time > Duration::from_secs(0) {
-                    self.status_report = format!("I think it is a new day.  Resetting.");
-                    self.screen_time = Duration::from_secs(0);
-                    for b in self.breaks.iter_mut() {
-                        b.last_done = Duration::from_secs(0);
+                    self.status_report = self.text(Message::Resumed(away));
+                    if let Some(Err(e)) = self.server.as_ref().map(|s| s.resumed(away)) {
+                        eprintln!("Unable to signal that you resumed: {e}");
                     }
+                    self.hook(hooks::Event::Resume, None, Some(away));
+                    if config.ask_if_working_after.is_some_and(|a| away > a)
+                        && !config.is_new_day(away)
+                        && !self.personal
+                    {
+                        // An unanswered question about an earlier absence
+                        // must have been a break.
+                        self.count_away(Kind::Break);
+                        self.away = Some((start, away));
+                        self.say(&self.config.language.spoken(Message::WelcomeBack));
+                    } else {
+                        self.record(Entry::new(Kind::Break, start, away));
+                    }
+                } else if config.is_new_day(t)
+                    && (self.screen_time > Duration::from_secs(0)
+                        || self.personal_time > Duration::from_secs(0))
+                {
+                    self.status_report = self.text(Message::NewDay);
+                    self.reset_day();
                 } else {
-                    self.latest_update = format!("You've been idle for {}", t.pretty());
+                    self.latest_update = self.text(Message::IdleFor(t));
                     std::io::stdout().flush()?;
                 }
             }
@@ -286,11 +1312,28 @@ impl State {
 }
 
 fn main() -> anyhow::Result<()> {
-    let state = State::load()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a != "tui") {
+        return cli::run(&args);
+    }
+    let mut state = State::load()?;
+    if let Some(tts) = &state.tts {
+        voice::list(&tts.lock().unwrap());
+    }
+    if !args.is_empty() {
+        return tui::run(state);
+    }
 
     let main_window = WindowDesc::new(ui_builder())
         .title(LocalizedString::new("breaks").with_placeholder("Breaks and workday reminders"));
-    AppLauncher::with_window(main_window)
+    let launcher = AppLauncher::with_window(main_window);
+    let sink = launcher.get_external_handle();
+    let act = move |action| {
+        sink.submit_command(ACTION, action, druid::Target::Auto)
+            .ok();
+    };
+    start_services(&mut state, act);
+    launcher
         .delegate(Delegate)
         .log_to_console()
         .launch(state)
@@ -298,9 +1341,44 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn idle_time() -> anyhow::Result<Duration> {
-    let idle = user_idle::UserIdle::get_time().map_err(|e| anyhow::anyhow!("{}", e))?;
-    Ok(idle.duration())
+/// Starts whatever the config asks for besides the frontend, calling `act`
+/// with what they ask of us.
+fn start_services(state: &mut State, act: impl Fn(Action) + Clone + Send + Sync + 'static) {
+    if state.config.notifications {
+        match notify::Notifier::new(act.clone()) {
+            Ok(n) => state.notifier = Some(n),
+            Err(e) => eprintln!("Unable to show notifications: {e}"),
+        }
+    }
+    if let Some(port) = state.config.metrics_port {
+        if let Err(e) = state.metrics.serve(port) {
+            eprintln!("Unable to serve metrics on port {port}: {e}");
+        }
+    }
+    if let Some(port) = state.config.dashboard_port {
+        let dashboard = dashboard::Dashboard::new(state.history_path.clone());
+        match dashboard.serve(port, act.clone()) {
+            Ok(_) => state.dashboard = Some(dashboard),
+            Err(e) => eprintln!("Unable to serve the dashboard on port {port}: {e}"),
+        }
+    }
+    if let Some(config) = state.config.mqtt.clone() {
+        state.mqtt = Some(mqtt::Publisher::start(config, act.clone()));
+    }
+    if state.config.dbus_service {
+        match dbus::Server::new(act) {
+            Ok(s) => state.server = Some(s),
+            Err(e) => eprintln!("Unable to start the D-Bus service: {e}"),
+        }
+    }
+}
+
+/// A random number, for when we want some variety.
+fn random() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
 }
 
 fn am_in_meet() -> bool {
@@ -334,56 +1412,171 @@ fn ui_builder() -> impl Widget<State> {
             .with_text_size(24.0);
     let latest = druid::widget::Label::new(move |s: &State, _: &Env| s.latest_update.clone())
         .with_text_size(18.0);
+    let pomodoro = druid::widget::Label::new(move |s: &State, _: &Env| s.pomodoro_report.clone())
+        .with_text_size(18.0);
+    let microbreak = druid::widget::Label::new(move |s: &State, _: &Env| s.microbreak_report())
+        .with_text_size(24.0);
     let done = druid::widget::DisabledIf::new(
-        Button::new("Done").on_click(move |ctx, state: &mut State, _| {
-            if let Some(prompt) = std::mem::replace(&mut state.am_prompting, None) {
-                state.status_report = format!("Well done with the {}!", prompt);
-                ctx.submit_command(druid::commands::SHOW_ALL);
-            }
-        }),
-        |state, _| state.am_prompting.is_none(),
+        Button::dynamic(|s: &State, _| s.text(Message::Done)).on_click(
+            move |ctx, state: &mut State, _| {
+                if state.done() {
+                    ctx.submit_command(druid::commands::SHOW_ALL);
+                }
+            },
+        ),
+        |state, _| !state.can_be_done(),
     );
-    let delay_15m = druid::widget::DisabledIf::new(
-        Button::new("Delay 15 minutes").on_click(move |_, state: &mut State, _| {
-            if let Some(prompt) = &state.am_prompting {
-                state.last_prompt = Instant::now() + Duration::from_secs(15 * 60);
-                state.status_report = format!("Putting off {}...", prompt);
-            }
-        }),
-        |state, _| state.am_prompting.is_none(),
+    let routine = druid::widget::Label::new(move |s: &State, _: &Env| s.routine_report())
+        .with_text_size(24.0);
+    let start_routine = druid::widget::DisabledIf::new(
+        Button::dynamic(|s: &State, _| s.text(Message::StartRoutine))
+            .on_click(|_, state: &mut State, _| state.start_routine()),
+        |state, _| state.routine.is_none() || state.doing_routine(),
     );
-    let delay_1h = druid::widget::DisabledIf::new(
-        Button::new("Delay 1 hour").on_click(move |_, state: &mut State, _| {
-            if let Some(prompt) = &state.am_prompting {
-                state.last_prompt = Instant::now() + Duration::from_secs(60 * 60);
-                state.status_report = format!("Putting off {}...", prompt);
-            }
-        }),
-        |state, _| state.am_prompting.is_none(),
+    let skip_routine = druid::widget::DisabledIf::new(
+        Button::dynamic(|s: &State, _| s.text(Message::SkipRoutine))
+            .on_click(|_, state: &mut State, _| state.skip_routine()),
+        |state, _| state.routine.is_none(),
+    );
+    let delay = |time| {
+        druid::widget::DisabledIf::new(
+            Button::dynamic(move |s: &State, _| s.text(Message::Delay(time)))
+                .on_click(move |_, state: &mut State, _| state.delay(time)),
+            |state, _| state.am_prompting.is_none(),
+        )
+    };
+    let [delay_15m, delay_1h] = notify::DELAYS.map(delay);
+
+    let away = druid::widget::Label::new(move |s: &State, _: &Env| {
+        if let Some((_, away)) = s.away {
+            s.text(Message::AwayQuestion(away))
+        } else {
+            "".to_string()
+        }
+    })
+    .with_text_size(24.0);
+    let personal = Button::dynamic(|s: &State, _| {
+        if s.personal {
+            s.text(Message::BackToWork)
+        } else {
+            s.text(Message::PersonalTime)
+        }
+    })
+    .on_click(|_, state: &mut State, _| state.set_personal(!state.personal));
+    let focus = Button::dynamic(|s: &State, _| {
+        if s.focusing() {
+            s.text(Message::EndFocus)
+        } else {
+            s.text(Message::Focus(s.config.focus_time))
+        }
+    })
+    .on_click(|_, state: &mut State, _| {
+        if state.focusing() {
+            state.end_focus();
+        } else {
+            state.focus(state.config.focus_time, false);
+        }
+    });
+    let confirm_focus = druid::widget::DisabledIf::new(
+        Button::dynamic(|s: &State, _| s.text(Message::FocusAnyway)).on_click(
+            |_, state: &mut State, _| {
+                if let Some(time) = state.confirm_focus {
+                    state.focus(time, true);
+                }
+            },
+        ),
+        |state, _| state.confirm_focus.is_none(),
     );
+    let mut away_buttons = Flex::row();
+    for (label, kind) in [
+        (Message::Break, Kind::Break),
+        (Message::Meeting, Kind::Meeting),
+        (Message::OfflineWork, Kind::OfflineWork),
+    ] {
+        away_buttons.add_child(druid::widget::DisabledIf::new(
+            Button::dynamic(move |s: &State, _| s.text(label))
+                .on_click(move |_, state: &mut State, _| state.spent_away(kind)),
+            |state, _| state.away.is_none(),
+        ));
+    }
 
     let mut col = Flex::column();
     col.add_child(prompt);
+    col.add_child(routine);
+    col.add_child(microbreak);
     col.add_spacer(8.0);
     col.add_child(status_report);
     col.add_spacer(8.0);
     col.add_child(latest);
+    col.add_child(pomodoro);
     col.add_spacer(8.0);
     let mut buttons = Flex::row();
+    buttons.add_child(start_routine);
+    buttons.add_child(skip_routine);
     buttons.add_child(done);
     buttons.add_child(delay_15m);
     buttons.add_child(delay_1h);
+    buttons.add_child(personal);
+    let mut focus_buttons = Flex::row();
+    focus_buttons.add_child(focus);
+    focus_buttons.add_child(confirm_focus);
     col.add_child(buttons);
+    col.add_child(focus_buttons);
+    col.add_spacer(8.0);
+    col.add_child(away);
+    col.add_child(away_buttons);
     col.add_child(TimerWidget {
         timer_id: TimerToken::INVALID,
+        last_update: Instant::now(),
+        microbreak_window: None,
     });
     Align::centered(col)
 }
 
-impl AppDelegate<State> for Delegate {}
+/// The microbreak and its countdown, in a window of its own.
+fn microbreak_builder() -> impl Widget<State> {
+    Align::centered(
+        druid::widget::Label::new(move |s: &State, _: &Env| s.microbreak_report())
+            .with_text_size(24.0),
+    )
+}
+
+/// Something asked of us from outside the window.
+const ACTION: druid::Selector<Action> = druid::Selector::new("breaks.action");
+
+impl AppDelegate<State> for Delegate {
+    fn command(
+        &mut self,
+        ctx: &mut druid::DelegateCtx,
+        _: druid::Target,
+        cmd: &druid::Command,
+        state: &mut State,
+        _: &Env,
+    ) -> druid::Handled {
+        let Some(action) = cmd.get(ACTION) else {
+            return druid::Handled::No;
+        };
+        if state.act(*action) {
+            ctx.submit_command(druid::commands::SHOW_ALL);
+        }
+        state.update_notification();
+        state.publish();
+        druid::Handled::Yes
+    }
+}
+
+/// The longest you can put off a prompt or focus for, whatever D-Bus or the
+/// dashboard ask for.
+const LONGEST_WAIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often to check on you.
+const UPDATE_EVERY: Duration = Duration::from_secs(10);
 
 struct TimerWidget {
     timer_id: TimerToken,
+    last_update: Instant,
+    /// The window showing the microbreak, if there is one.
+    microbreak_window: Option<druid::WindowId>,
 }
 impl Widget<State> for TimerWidget {
     fn event(
@@ -396,24 +1589,50 @@ impl Widget<State> for TimerWidget {
         match event {
             druid::Event::WindowConnected => {
                 // Start the timer when the application launches
-                self.timer_id = ctx.request_timer(Duration::from_secs(10));
+                self.timer_id = ctx.request_timer(UPDATE_EVERY);
             }
             druid::Event::Timer(id) => {
                 if *id == self.timer_id {
-                    data.update().unwrap();
-                    print!("\rupdate: {}", data.latest_update);
-                    std::io::stdout().flush().ok();
-                    ctx.request_layout();
-
-                    if data.am_prompting.is_some() && data.after_last_prompt() {
-                        ctx.submit_command(druid::commands::SHOW_WINDOW);
-                        if data.since_last_prompt() > data.config.when_to_emphasize_break {
+                    let check_in = self.last_update.elapsed() >= UPDATE_EVERY;
+                    if check_in {
+                        self.last_update = Instant::now();
+                    }
+                    let attention = data.tick(check_in);
+                    if check_in {
+                        print!("\rupdate: {}", data.latest_update);
+                        std::io::stdout().flush().ok();
+                    }
+                    // Notifications get your attention without the window.
+                    if data.notifier.is_none() {
+                        if attention.show {
+                            ctx.submit_command(druid::commands::SHOW_WINDOW);
+                        }
+                        if attention.insist {
                             ctx.submit_command(druid::commands::HIDE_OTHERS);
-                            data.last_prompt = Instant::now();
                         }
-                        data.announce();
                     }
-                    self.timer_id = ctx.request_timer(Duration::from_secs(10));
+                    // Without notifications, microbreaks get a little window
+                    // of their own, over the others.
+                    let overlay = data.microbreak.is_some() && data.notifier.is_none();
+                    match (overlay, self.microbreak_window) {
+                        (true, None) => {
+                            let window = WindowDesc::new(microbreak_builder())
+                                .title(LocalizedString::new("breaks-microbreak"))
+                                .window_size((420.0, 90.0))
+                                .resizable(false)
+                                .show_titlebar(false)
+                                .set_always_on_top(true);
+                            self.microbreak_window = Some(window.id);
+                            ctx.new_window(window);
+                        }
+                        (false, Some(id)) => {
+                            ctx.submit_command(druid::commands::CLOSE_WINDOW.to(id));
+                            self.microbreak_window = None;
+                        }
+                        _ => (),
+                    }
+                    ctx.request_layout();
+                    self.timer_id = ctx.request_timer(data.tick_every());
                 }
             }
             _ => (),
@@ -433,10 +1652,185 @@ impl Widget<State> for TimerWidget {
         _: &Env,
     ) -> druid::Size {
         if self.timer_id == TimerToken::INVALID {
-            self.timer_id = ctx.request_timer(Duration::from_secs(10));
+            self.timer_id = ctx.request_timer(UPDATE_EVERY);
         }
         druid::Size::new(0.0, 0.0)
     }
 
     fn paint(&mut self, _: &mut druid::PaintCtx, _: &State, _: &Env) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn activities() {
+        let mut b = Break::new("", Duration::from_secs(60 * 60));
+        assert_eq!(b.choose_prompt(&[], random()), "");
+
+        b.activities = vec![
+            "Walk".to_string(),
+            "Stretch".to_string(),
+            "Plank".to_string(),
+        ];
+        let done = |a: &str, hours_ago: i64| Entry {
+            start: chrono::Local::now() - chrono::Duration::hours(hours_ago),
+            kind: Kind::Done,
+            duration: Duration::from_secs(0),
+            note: a.to_string(),
+        };
+        let history = vec![done("Walk", 1000), done("Walk", 1), done("Stretch", 2)];
+        let mut counts = std::collections::HashMap::new();
+        let mut previous = None;
+        for r in 0..1000 {
+            let chosen = b.choose_prompt(&history, r * 7919);
+            assert_ne!(Some(&chosen), previous.as_ref());
+            *counts.entry(chosen.clone()).or_insert(0) += 1;
+            previous = Some(chosen);
+        }
+        // Plank was never done, so it comes up most, while the walk was just taken.
+        assert!(counts["Plank"] > counts["Stretch"]);
+        assert!(counts["Stretch"] > counts["Walk"]);
+    }
+
+    #[test]
+    fn absurd_times() {
+        let mut state = State::default();
+        (state.tts, state.player) = (None, sound::Player::null(Default::default()));
+        state.am_prompting = Some("Stretch".to_string());
+        state.delay(Duration::MAX);
+        assert!(state.last_prompt <= Instant::now() + LONGEST_WAIT);
+        state.focus(Duration::MAX, true);
+        assert!(state.focusing());
+        state.focus(Duration::from_secs(u64::MAX), false);
+        assert_eq!(state.confirm_focus, Some(LONGEST_WAIT));
+
+        state.history_path =
+            std::env::temp_dir().join(format!("breaks-test-add-{}.jsonl", std::process::id()));
+        for _ in 0..2 {
+            state.handle(Request::Add {
+                duration: Duration::MAX,
+                reason: String::new(),
+            });
+        }
+        assert_eq!(state.screen_time, 2 * inbox::MOST_AT_ONCE);
+        std::fs::remove_file(&state.history_path).ok();
+    }
+
+    #[test]
+    fn done_after_routine() {
+        let mut state = State::default();
+        (state.tts, state.player) = (None, sound::Player::null(Default::default()));
+        state.history_path =
+            std::env::temp_dir().join(format!("breaks-test-routine-{}.jsonl", std::process::id()));
+        let prompt = Prompt {
+            routine: vec![Step {
+                text: "Touch your toes".to_string(),
+                time: Duration::from_secs(30),
+            }],
+            ..Prompt::new("Stretch", "Stretch".to_string(), "Stretch".to_string())
+        };
+        state.prompt(Cue::Prompt, prompt);
+        // However it's asked for, as from D-Bus.
+        assert!(!state.act(Action::Done));
+        assert_eq!(state.am_prompting.as_deref(), Some("Stretch"));
+        state.skip_routine();
+        assert!(state.act(Action::Done));
+        assert!(state.am_prompting.is_none());
+        std::fs::remove_file(&state.history_path).ok();
+    }
+
+    #[test]
+    fn done_activities() {
+        let mut state = State::default();
+        (state.tts, state.player) = (None, sound::Player::null(Default::default()));
+        state.history_path =
+            std::env::temp_dir().join(format!("breaks-test-history-{}.jsonl", std::process::id()));
+        std::fs::remove_file(&state.history_path).ok();
+
+        let mut b = Break::new("", Duration::from_secs(60 * 60));
+        b.activities = vec!["Stretch ({count})".to_string(), "Walk".to_string()];
+        let chosen = b.choose_prompt(&[], 0);
+        assert_eq!(chosen, "Stretch ({count})");
+        let hour = Duration::from_secs(60 * 60);
+        let values = template::Values::now(hour, 8 * hour, hour, 1, Language::English);
+        let prompt = Prompt {
+            activities: vec![chosen.clone()],
+            ..Prompt::new(
+                &b.label(),
+                template::render(&chosen, &values),
+                template::render_spoken(&chosen, &values),
+            )
+        };
+        state.prompt(Cue::Prompt, prompt);
+        assert_eq!(state.am_prompting.as_deref(), Some("Stretch (1)"));
+        assert!(state.done());
+
+        let history = history::read(&state.history_path).unwrap();
+        std::fs::remove_file(&state.history_path).ok();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].note, "Stretch ({count})");
+        // Having just stretched, you're due a walk, even if we could stretch
+        // again.
+        b.last_activity = None;
+        assert_eq!(b.choose_prompt(&history, 1), "Walk");
+    }
+
+    #[test]
+    fn default_config() {
+        let toml = toml::ser::to_string_pretty(&Config::default()).unwrap();
+        let config: Config = toml::de::from_str(&toml).unwrap();
+        assert_eq!(config.workday, Config::default().workday);
+        assert_eq!(config.breaks.len(), Config::default().breaks.len());
+
+        let config = Config {
+            ask_if_working_after: Some(Duration::from_secs(20 * 60)),
+            ..Config::default()
+        };
+        let toml = toml::ser::to_string_pretty(&config).unwrap();
+        assert!(toml.contains("ask_if_working_after = '20 minutes'"));
+        let config: Config = toml::de::from_str(&toml).unwrap();
+        assert_eq!(
+            config.ask_if_working_after,
+            Some(Duration::from_secs(20 * 60))
+        );
+
+        let config = Config {
+            pomodoro: Some(Default::default()),
+            ..Config::default()
+        };
+        let toml = toml::ser::to_string_pretty(&config).unwrap();
+        let config: Config = toml::de::from_str(&toml).unwrap();
+        assert!(config.pomodoro.is_some());
+        let config: Config = toml::de::from_str("[pomodoro]\nwork = '50m'").unwrap();
+        assert!(config.pomodoro.is_some());
+
+        let config: Config = toml::de::from_str(
+            "[[breaks]]
+            prompt = 'Time for a 7-minute exersize'
+            after = '3 hours'
+            [[breaks.routine]]
+            text = 'Jumping jacks'
+            time = '30 seconds'
+            [[breaks.routine]]
+            text = 'Rest'
+            time = '10s'",
+        )
+        .unwrap();
+        assert_eq!(config.breaks[0].routine.len(), 2);
+        assert_eq!(config.breaks[0].routine[1].time, Duration::from_secs(10));
+
+        let config: Config = toml::de::from_str("workday = \"6 hours\"").unwrap();
+        assert_eq!(config.workday, Duration::from_secs(6 * 60 * 60));
+        assert_eq!(config.idle_sources, vec![IdleSource::Desktop]);
+
+        assert!(Config::default().validate().is_ok());
+        let config: Config = toml::de::from_str("end_of_day_prompt = '{worked} {oops}'").unwrap();
+        assert!(config.validate().is_err());
+
+        let config: Config = toml::de::from_str("language = 'es'").unwrap();
+        assert_eq!(config.language, Language::Spanish);
+        assert!(toml::de::from_str::<Config>("language = 'xx'").is_err());
+    }
+}
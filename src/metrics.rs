@@ -0,0 +1,224 @@
+//! Prometheus metrics, served on localhost.
+
+use crate::localhost;
+use crate::snapshot::Snapshot;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The upper bounds of the session length histogram, in minutes.
+const SESSION_BUCKETS: [u64; 8] = [5, 15, 30, 45, 60, 90, 120, 180];
+
+/// What became of a prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Issued,
+    Acknowledged,
+    Snoozed,
+    /// You ignored it for long enough that we insisted.
+    Escalated,
+}
+
+const OUTCOMES: [(Outcome, &str, &str); 4] = [
+    (Outcome::Issued, "issued", "Prompts shown"),
+    (Outcome::Acknowledged, "acknowledged", "Prompts marked done"),
+    (Outcome::Snoozed, "snoozed", "Prompts put off"),
+    (
+        Outcome::Escalated,
+        "escalated",
+        "Prompts emphasized after being ignored",
+    ),
+];
+
+#[derive(Default)]
+struct Counts {
+    snapshot: Snapshot,
+    /// By break name and outcome.
+    prompts: BTreeMap<(String, Outcome), u64>,
+    /// How many sessions were no longer than each of `SESSION_BUCKETS`.
+    sessions: [u64; SESSION_BUCKETS.len()],
+    session_count: u64,
+    session_seconds: u64,
+}
+
+/// Counts what happens, for Prometheus to scrape.
+#[derive(Clone, Default)]
+pub struct Metrics(Arc<Mutex<Counts>>);
+
+impl Metrics {
+    pub fn update(&self, snapshot: &Snapshot) {
+        self.0.lock().unwrap().snapshot = snapshot.clone();
+    }
+
+    pub fn count(&self, name: &str, outcome: Outcome) {
+        *self
+            .0
+            .lock()
+            .unwrap()
+            .prompts
+            .entry((name.to_string(), outcome))
+            .or_default() += 1;
+    }
+
+    /// Notes a stretch of work at the computer.
+    pub fn session(&self, length: Duration) {
+        let mut counts = self.0.lock().unwrap();
+        for (bucket, count) in SESSION_BUCKETS.iter().zip(counts.sessions.iter_mut()) {
+            if length.as_secs() <= bucket * 60 {
+                *count += 1;
+            }
+        }
+        counts.session_count += 1;
+        counts.session_seconds += length.as_secs();
+    }
+
+    /// The metrics in Prometheus' text format.
+    fn render(&self) -> String {
+        let counts = self.0.lock().unwrap();
+        let s = &counts.snapshot;
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: u64| {
+            writeln!(out, "# HELP breaks_{name} {help}").ok();
+            writeln!(out, "# TYPE breaks_{name} gauge").ok();
+            writeln!(out, "breaks_{name} {value}").ok();
+        };
+        gauge("worked_seconds", "Time worked today.", s.worked.as_secs());
+        gauge(
+            "workday_seconds",
+            "How long the workday is.",
+            s.workday.as_secs(),
+        );
+        gauge(
+            "idle",
+            "Whether you're away from the computer.",
+            s.idle as u64,
+        );
+        gauge(
+            "prompting",
+            "Whether there's a prompt waiting.",
+            s.prompt.is_some() as u64,
+        );
+
+        let names: BTreeSet<&str> = counts.prompts.keys().map(|(n, _)| n.as_str()).collect();
+        for (outcome, what, help) in OUTCOMES {
+            writeln!(out, "# HELP breaks_prompts_{what}_total {help}.").ok();
+            writeln!(out, "# TYPE breaks_prompts_{what}_total counter").ok();
+            for &name in &names {
+                let n = counts
+                    .prompts
+                    .get(&(name.to_string(), outcome))
+                    .unwrap_or(&0);
+                writeln!(
+                    out,
+                    "breaks_prompts_{what}_total{{break=\"{}\"}} {n}",
+                    escape(name)
+                )
+                .ok();
+            }
+        }
+
+        let name = "breaks_session_length_seconds";
+        writeln!(out, "# HELP {name} Stretches of work at the computer.").ok();
+        writeln!(out, "# TYPE {name} histogram").ok();
+        for (bucket, count) in SESSION_BUCKETS.iter().zip(counts.sessions) {
+            writeln!(out, "{name}_bucket{{le=\"{}\"}} {count}", bucket * 60).ok();
+        }
+        writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", counts.session_count).ok();
+        writeln!(out, "{name}_sum {}", counts.session_seconds).ok();
+        writeln!(out, "{name}_count {}", counts.session_count).ok();
+        out
+    }
+
+    /// Serves `/metrics` on `port` of localhost in the background, returning
+    /// the port, which is chosen for us if `port` is 0.
+    pub fn serve(&self, port: u16) -> anyhow::Result<u16> {
+        let server =
+            tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| anyhow::anyhow!("{e}"))?;
+        let port = server.server_addr().to_ip().map_or(port, |a| a.port());
+        let metrics = self.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if !localhost::for_us(&request, port) {
+                    localhost::refuse(request);
+                    continue;
+                }
+                let response = if request.url() == "/metrics" {
+                    let content_type = tiny_http::Header::from_bytes(
+                        "Content-Type",
+                        "text/plain; version=0.0.4; charset=utf-8",
+                    )
+                    .unwrap();
+                    tiny_http::Response::from_string(metrics.render()).with_header(content_type)
+                } else {
+                    tiny_http::Response::from_string("Not found").with_status_code(404)
+                };
+                request.respond(response).ok();
+            }
+        });
+        Ok(port)
+    }
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn metrics() {
+        let metrics = Metrics::default();
+        metrics.update(&Snapshot {
+            worked: Duration::from_secs(3600),
+            prompt: Some("Stretch".to_string()),
+            ..Default::default()
+        });
+        metrics.count("Stretch", Outcome::Issued);
+        metrics.count("Stretch", Outcome::Issued);
+        metrics.count("Stretch", Outcome::Acknowledged);
+        metrics.count("Say \"hi\"", Outcome::Snoozed);
+        metrics.session(Duration::from_secs(20 * 60));
+        metrics.session(Duration::from_secs(4 * 60 * 60));
+
+        let port = metrics.serve(0).unwrap();
+        let text = ureq::get(&format!("http://127.0.0.1:{port}/metrics"))
+            .call()
+            .unwrap()
+            .into_string()
+            .unwrap();
+        for line in [
+            "breaks_worked_seconds 3600",
+            "breaks_prompting 1",
+            "breaks_idle 0",
+            "breaks_prompts_issued_total{break=\"Stretch\"} 2",
+            "breaks_prompts_acknowledged_total{break=\"Stretch\"} 1",
+            "breaks_prompts_escalated_total{break=\"Stretch\"} 0",
+            "breaks_prompts_snoozed_total{break=\"Say \\\"hi\\\"\"} 1",
+            "breaks_session_length_seconds_bucket{le=\"900\"} 0",
+            "breaks_session_length_seconds_bucket{le=\"1800\"} 1",
+            "breaks_session_length_seconds_bucket{le=\"+Inf\"} 2",
+            "breaks_session_length_seconds_sum 15600",
+            "breaks_session_length_seconds_count 2",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line} not in\n{text}");
+        }
+        assert!(ureq::get(&format!("http://127.0.0.1:{port}/"))
+            .call()
+            .is_err());
+        // Nor for another site whose name leads here.
+        let status = ureq::get(&format!("http://127.0.0.1:{port}/metrics"))
+            .set("Host", &format!("example.com:{port}"))
+            .call()
+            .err()
+            .and_then(|e| e.into_response())
+            .map(|r| r.status());
+        assert_eq!(status, Some(403));
+    }
+}
@@ -0,0 +1,286 @@
+//! Publishing how things stand to an MQTT broker, for home automation, and
+//! taking commands back.
+
+use crate::action::Action;
+use crate::snapshot::Snapshot;
+use rumqttc::{Event, LastWill, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait before reconnecting to the broker.
+const RECONNECT_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    /// What our topics start with, as in "breaks/status".
+    prefix: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: "localhost".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            prefix: "breaks".to_string(),
+        }
+    }
+}
+
+impl Config {
+    fn topic(&self, name: &str) -> String {
+        format!("{}/{name}", self.prefix)
+    }
+}
+
+/// Understands a message on the command topic: "done", or "snooze" with an
+/// optional time like "snooze 10 minutes".
+fn parse(command: &str) -> Option<Action> {
+    let command = command.trim();
+    if command.eq_ignore_ascii_case("done") {
+        return Some(Action::Done);
+    }
+    let verb = command.get(..6)?;
+    if !verb.eq_ignore_ascii_case("snooze") {
+        return None;
+    }
+    match command[6..].trim() {
+        "" => Some(Action::Delay(crate::notify::DELAYS[0])),
+        time => crate::hours::parseme(time).ok().map(Action::Delay),
+    }
+}
+
+/// What to publish for `snapshot`, by topic name.
+fn messages(snapshot: &Snapshot) -> [(&'static str, String); 4] {
+    [
+        (
+            "status",
+            if snapshot.idle { "idle" } else { "working" }.to_string(),
+        ),
+        ("worked", snapshot.worked.as_secs().to_string()),
+        ("prompt", snapshot.prompt.clone().unwrap_or_default()),
+        ("end_of_day", snapshot.over_workday().to_string()),
+    ]
+}
+
+/// Keeps retained messages on the broker up to date.
+#[derive(Clone)]
+pub struct Publisher {
+    client: rumqttc::Client,
+    config: Config,
+    /// What the broker has, by topic name.
+    published: Arc<Mutex<BTreeMap<&'static str, String>>>,
+}
+
+impl Publisher {
+    /// Connects to the broker in the background, calling `act` with the
+    /// commands that come in.
+    pub fn start(config: Config, act: impl Fn(Action) + Send + 'static) -> Self {
+        let mut options = rumqttc::MqttOptions::new(
+            format!("breaks-{}", std::process::id()),
+            &config.host,
+            config.port,
+        );
+        options.set_keep_alive(Duration::from_secs(30));
+        // The broker says we're offline if we go away without saying so.
+        options.set_last_will(LastWill::new(
+            config.topic("status"),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+        let (client, mut connection) = rumqttc::Client::new(options, 16);
+        let published: Arc<Mutex<BTreeMap<&'static str, String>>> = Default::default();
+        let publisher = Publisher {
+            client: client.clone(),
+            config: config.clone(),
+            published: published.clone(),
+        };
+        let commands = config.topic("command");
+        std::thread::spawn(move || {
+            let mut connected = true;
+            for event in connection.iter() {
+                match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        connected = true;
+                        // Start afresh, in case the broker forgot.
+                        published.lock().unwrap().clear();
+                        if let Err(e) = client.try_subscribe(&commands, QoS::AtLeastOnce) {
+                            eprintln!("Unable to subscribe to {commands}: {e}");
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(p))) if p.topic == commands => {
+                        let command = String::from_utf8_lossy(&p.payload);
+                        match parse(&command) {
+                            Some(action) => act(action),
+                            None => eprintln!("Unknown MQTT command {command:?}"),
+                        }
+                    }
+                    Ok(_) => (),
+                    Err(e) => {
+                        if connected {
+                            eprintln!("Lost the MQTT broker, will keep trying: {e}");
+                            connected = false;
+                        }
+                        std::thread::sleep(RECONNECT_AFTER);
+                    }
+                }
+            }
+        });
+        publisher
+    }
+
+    /// Publishes whatever has changed.
+    pub fn update(&self, snapshot: &Snapshot) {
+        let mut published = self.published.lock().unwrap();
+        for (name, message) in messages(snapshot) {
+            if published.get(name) == Some(&message) {
+                continue;
+            }
+            let topic = self.config.topic(name);
+            // This only fails while we can't reach the broker, and we'll
+            // publish everything once we can.
+            if self
+                .client
+                .try_publish(topic, QoS::AtLeastOnce, true, message.as_bytes())
+                .is_ok()
+            {
+                published.insert(name, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::BytesMut;
+    use rumqttc::{ConnAck, ConnectReturnCode, PubAck, Publish, SubAck, SubscribeReasonCode};
+    use std::io::{Read, Write};
+    use std::sync::mpsc;
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse("done"), Some(Action::Done));
+        assert_eq!(
+            parse(" Snooze\n"),
+            Some(Action::Delay(crate::notify::DELAYS[0]))
+        );
+        assert_eq!(
+            parse("snooze 10 minutes"),
+            Some(Action::Delay(Duration::from_secs(600)))
+        );
+        assert_eq!(parse("snooze for a bit"), None);
+        // Too long to be a duration at all.
+        assert_eq!(parse("snooze 1e300 hours"), None);
+        assert_eq!(parse("lunch"), None);
+    }
+
+    /// Acts as a broker for one client, sending it `command` once it
+    /// subscribes, and passing on what it publishes.
+    fn fake_broker(command: &'static str) -> (u16, mpsc::Receiver<Publish>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = BytesMut::new();
+            loop {
+                let packet = match rumqttc::mqttbytes::v4::read(&mut buf, 1 << 20) {
+                    Ok(packet) => packet,
+                    Err(rumqttc::mqttbytes::Error::InsufficientBytes(_)) => {
+                        let mut chunk = [0; 1024];
+                        match stream.read(&mut chunk) {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                        continue;
+                    }
+                    Err(e) => panic!("{e:?}"),
+                };
+                let mut out = BytesMut::new();
+                match packet {
+                    Packet::Connect(_) => {
+                        ConnAck::new(ConnectReturnCode::Success, false)
+                            .write(&mut out)
+                            .unwrap();
+                    }
+                    Packet::Subscribe(s) => {
+                        let codes = vec![SubscribeReasonCode::Success(QoS::AtLeastOnce)];
+                        SubAck::new(s.pkid, codes).write(&mut out).unwrap();
+                        Publish::new(&s.filters[0].path, QoS::AtMostOnce, command)
+                            .write(&mut out)
+                            .unwrap();
+                    }
+                    Packet::Publish(p) => {
+                        PubAck::new(p.pkid).write(&mut out).unwrap();
+                        tx.send(p).ok();
+                    }
+                    _ => (),
+                }
+                stream.write_all(&out).unwrap();
+            }
+        });
+        (port, rx)
+    }
+
+    #[test]
+    fn broker() {
+        let (port, published) = fake_broker("snooze 5 minutes");
+        let config: Config = toml::from_str(&format!(
+            "host = '127.0.0.1'\nport = {port}\nprefix = 'desk'"
+        ))
+        .unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let publisher = Publisher::start(config, move |a| {
+            tx.lock().unwrap().send(a).unwrap();
+        });
+        let wait = Duration::from_secs(5);
+        assert_eq!(
+            rx.recv_timeout(wait),
+            Ok(Action::Delay(Duration::from_secs(300)))
+        );
+
+        let mut snapshot = Snapshot {
+            worked: Duration::from_secs(9 * 60 * 60),
+            workday: Duration::from_secs(8 * 60 * 60),
+            prompt: Some("Stretch".to_string()),
+            ..Default::default()
+        };
+        publisher.update(&snapshot);
+        let mut got = BTreeMap::new();
+        for _ in 0..4 {
+            let p = published.recv_timeout(wait).unwrap();
+            assert!(p.retain);
+            got.insert(p.topic, String::from_utf8_lossy(&p.payload).to_string());
+        }
+        assert_eq!(got["desk/status"], "working");
+        assert_eq!(got["desk/worked"], "32400");
+        assert_eq!(got["desk/prompt"], "Stretch");
+        assert_eq!(got["desk/end_of_day"], "true");
+
+        // Only what changed is published again.
+        snapshot.idle = true;
+        publisher.update(&snapshot);
+        let p = published.recv_timeout(wait).unwrap();
+        assert_eq!(
+            (p.topic.as_str(), &p.payload[..]),
+            ("desk/status", &b"idle"[..])
+        );
+        assert!(published.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
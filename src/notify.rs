@@ -0,0 +1,356 @@
+use crate::action::Action;
+use crate::lang::{Language, Message};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, zbus::zvariant::Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    fn close_notification(&self, id: u32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// How long you can put off a prompt for, in the window or a notification.
+pub const DELAYS: [Duration; 2] = [Duration::from_secs(15 * 60), Duration::from_secs(60 * 60)];
+
+/// The key of a notification button, which is "done", "reset-day", or
+/// "delay-" or "focus-" and a number of seconds.
+fn key(action: Action) -> String {
+    match action {
+        Action::Done => "done".to_string(),
+        Action::Delay(t) => format!("delay-{}", t.as_secs()),
+        Action::Focus(t) => format!("focus-{}", t.as_secs()),
+        Action::ResetDay => "reset-day".to_string(),
+    }
+}
+
+fn parse(key: &str) -> Option<Action> {
+    let secs = |s: &str| s.parse().ok().map(Duration::from_secs);
+    if let Some(s) = key.strip_prefix("delay-") {
+        return secs(s).map(Action::Delay);
+    }
+    if let Some(s) = key.strip_prefix("focus-") {
+        return secs(s).map(Action::Focus);
+    }
+    match key {
+        "done" => Some(Action::Done),
+        "reset-day" => Some(Action::ResetDay),
+        _ => None,
+    }
+}
+
+/// Shows prompts as desktop notifications.
+#[derive(Clone)]
+pub struct Notifier {
+    proxy: NotificationsProxyBlocking<'static>,
+    /// The notification we're showing, if any.
+    shown: Arc<Mutex<Option<u32>>>,
+    /// The microbreak we're showing, if any.
+    microbreak: Arc<Mutex<Option<u32>>>,
+}
+
+impl Notifier {
+    /// Talks to the notification server on the session bus, calling
+    /// `on_action` with what you choose in our notifications.
+    pub fn new(on_action: impl Fn(Action) + Send + 'static) -> zbus::Result<Self> {
+        Notifier::connect(&zbus::blocking::Connection::session()?, on_action)
+    }
+
+    fn connect(
+        connection: &zbus::blocking::Connection,
+        on_action: impl Fn(Action) + Send + 'static,
+    ) -> zbus::Result<Self> {
+        let proxy = NotificationsProxyBlocking::new(connection)?;
+        let actions = proxy.receive_action_invoked()?;
+        let closed = proxy.receive_notification_closed()?;
+        let shown: Arc<Mutex<Option<u32>>> = Default::default();
+        let microbreak: Arc<Mutex<Option<u32>>> = Default::default();
+        let ours = shown.clone();
+        std::thread::spawn(move || {
+            for signal in actions {
+                let Ok(args) = signal.args() else { continue };
+                if *ours.lock().unwrap() != Some(*args.id()) {
+                    continue;
+                }
+                if let Some(action) = parse(args.action_key()) {
+                    on_action(action);
+                }
+            }
+        });
+        // Forget notifications once they're gone, whether they expired or
+        // you dismissed them.
+        let slots = [shown.clone(), microbreak.clone()];
+        std::thread::spawn(move || {
+            for signal in closed {
+                let Ok(args) = signal.args() else { continue };
+                for slot in &slots {
+                    let mut slot = slot.lock().unwrap();
+                    if *slot == Some(*args.id()) {
+                        *slot = None;
+                    }
+                }
+            }
+        });
+        Ok(Notifier {
+            proxy,
+            shown,
+            microbreak,
+        })
+    }
+
+    /// Shows `prompt`, in place of whatever we showed before, with a Done
+    /// button if `can_be_done`.
+    pub fn show(&self, prompt: &str, can_be_done: bool, language: Language) -> zbus::Result<()> {
+        let mut actions = Vec::new();
+        if can_be_done {
+            actions.push(key(Action::Done));
+            actions.push(language.text(Message::Done));
+        }
+        for t in DELAYS {
+            actions.push(key(Action::Delay(t)));
+            actions.push(language.text(Message::Delay(t)));
+        }
+        let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+        // Critical notifications stay up until you do something about them.
+        let hints = HashMap::from([("urgency", zbus::zvariant::Value::U8(2))]);
+        self.replace(&self.shown, prompt, &actions, hints)
+    }
+
+    /// Whether our notification is still up.
+    pub fn showing(&self) -> bool {
+        self.shown.lock().unwrap().is_some()
+    }
+
+    /// Takes down our notification, if there is one.
+    pub fn close(&self) -> zbus::Result<()> {
+        Notifier::take_down(&self.proxy, &self.shown)
+    }
+
+    /// Shows a microbreak and its countdown, which need no answer, in place
+    /// of the microbreak we showed before.
+    pub fn show_microbreak(&self, text: &str) -> zbus::Result<()> {
+        // Quietly, and not to be kept once it's gone.
+        let hints = HashMap::from([
+            ("urgency", zbus::zvariant::Value::U8(0)),
+            ("transient", zbus::zvariant::Value::Bool(true)),
+        ]);
+        self.replace(&self.microbreak, text, &[], hints)
+    }
+
+    pub fn close_microbreak(&self) -> zbus::Result<()> {
+        Notifier::take_down(&self.proxy, &self.microbreak)
+    }
+
+    /// Shows `body` in place of the notification in `slot`.
+    fn replace(
+        &self,
+        slot: &Mutex<Option<u32>>,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, zbus::zvariant::Value<'_>>,
+    ) -> zbus::Result<()> {
+        let mut shown = slot.lock().unwrap();
+        let id = self.proxy.notify(
+            "breaks",
+            shown.unwrap_or(0),
+            "",
+            "Breaks",
+            body,
+            actions,
+            hints,
+            0,
+        )?;
+        *shown = Some(id);
+        Ok(())
+    }
+
+    fn take_down(
+        proxy: &NotificationsProxyBlocking<'static>,
+        slot: &Mutex<Option<u32>>,
+    ) -> zbus::Result<()> {
+        if let Some(id) = slot.lock().unwrap().take() {
+            proxy.close_notification(id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testbus::PrivateBus;
+    use std::sync::mpsc;
+
+    const PATH: &str = "/org/freedesktop/Notifications";
+    const INTERFACE: &str = "org.freedesktop.Notifications";
+
+    /// The body and actions of a notification.
+    type Notification = (String, Vec<String>);
+
+    /// Remembers the notifications it's asked to show.
+    #[derive(Default)]
+    struct FakeServer {
+        shown: Arc<Mutex<Vec<Notification>>>,
+        closed: Arc<Mutex<Vec<u32>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl FakeServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            _replaces_id: u32,
+            _app_icon: String,
+            _summary: String,
+            body: String,
+            actions: Vec<String>,
+            _hints: HashMap<String, zbus::zvariant::OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            self.shown.lock().unwrap().push((body, actions));
+            7
+        }
+        fn close_notification(&self, id: u32) {
+            self.closed.lock().unwrap().push(id);
+        }
+    }
+
+    #[test]
+    fn actions() {
+        assert_eq!(parse("done"), Some(Action::Done));
+        assert_eq!(
+            parse(&key(Action::Delay(DELAYS[1]))),
+            Some(Action::Delay(DELAYS[1]))
+        );
+        for action in [
+            Action::Done,
+            Action::Delay(DELAYS[0]),
+            Action::Focus(Duration::from_secs(25 * 60)),
+            Action::ResetDay,
+        ] {
+            assert_eq!(parse(&key(action)), Some(action));
+        }
+        assert_eq!(parse("delay-soon"), None);
+        assert_eq!(parse("default"), None);
+    }
+
+    #[test]
+    fn fake_server() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("No dbus-daemon, skipping");
+            return;
+        };
+        let fake = FakeServer::default();
+        let (shown, closed) = (fake.shown.clone(), fake.closed.clone());
+        let server = bus
+            .builder()
+            .name(INTERFACE)
+            .unwrap()
+            .serve_at(PATH, fake)
+            .unwrap()
+            .build()
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let notifier = Notifier::connect(&bus.builder().build().unwrap(), move |a| {
+            tx.lock().unwrap().send(a).unwrap();
+        })
+        .unwrap();
+
+        notifier.show("Stretch", true, Language::English).unwrap();
+        let first = shown.lock().unwrap()[0].clone();
+        assert_eq!(first.0, "Stretch");
+        assert_eq!(
+            first.1,
+            vec![
+                "done",
+                "Done",
+                "delay-900",
+                "Delay 15 minutes",
+                "delay-3600",
+                "Delay 1 hour"
+            ]
+        );
+
+        let invoke = |id: u32, key: &str| {
+            server
+                .emit_signal(None::<&str>, PATH, INTERFACE, "ActionInvoked", &(id, key))
+                .unwrap();
+        };
+        // Someone else's notification.
+        invoke(8, "done");
+        invoke(7, "delay-900");
+        let wait = Duration::from_secs(5);
+        assert_eq!(rx.recv_timeout(wait), Ok(Action::Delay(DELAYS[0])));
+        invoke(7, "done");
+        assert_eq!(rx.recv_timeout(wait), Ok(Action::Done));
+
+        // Dismissed, with someone else's closed first.
+        let dismiss = |id: u32| {
+            server
+                .emit_signal(
+                    None::<&str>,
+                    PATH,
+                    INTERFACE,
+                    "NotificationClosed",
+                    &(id, 2u32),
+                )
+                .unwrap();
+        };
+        dismiss(8);
+        dismiss(7);
+        let start = std::time::Instant::now();
+        while notifier.showing() && start.elapsed() < wait {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!notifier.showing());
+        // With a routine still to do, it can't be done yet.
+        notifier.show("Stretch", false, Language::English).unwrap();
+        assert!(notifier.showing());
+        let last = shown.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(
+            last.1,
+            vec![
+                "delay-900",
+                "Delay 15 minutes",
+                "delay-3600",
+                "Delay 1 hour"
+            ]
+        );
+
+        notifier.close().unwrap();
+        assert_eq!(*closed.lock().unwrap(), vec![7]);
+        // Once closed, its actions don't count.
+        invoke(7, "done");
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+
+        notifier.show_microbreak("Look away (20 seconds)").unwrap();
+        let last = shown.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last, ("Look away (20 seconds)".to_string(), vec![]));
+        notifier.close_microbreak().unwrap();
+        assert_eq!(*closed.lock().unwrap(), vec![7, 7]);
+    }
+}
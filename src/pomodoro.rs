@@ -0,0 +1,245 @@
+use crate::hours;
+use crate::lang::{Language, Message};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// If you've been idle less than this, you're at the computer.
+const ACTIVE: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(with = "hours")]
+    work: Duration,
+    #[serde(with = "hours")]
+    short_break: Duration,
+    #[serde(with = "hours")]
+    long_break: Duration,
+    /// How many pomodoros to do before a long break.
+    long_break_every: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            work: Duration::from_secs(25 * 60),
+            short_break: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            long_break_every: 4,
+        }
+    }
+}
+
+impl Config {
+    pub fn break_time(&self, long: bool) -> Duration {
+        if long {
+            self.long_break
+        } else {
+            self.short_break
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Interval {
+    /// Waiting for you to start working.
+    Waiting,
+    Working(Instant),
+    Resting {
+        since: Instant,
+        long: bool,
+        /// Whether you've been idle long enough to count the break as taken.
+        rested: bool,
+    },
+}
+
+/// Something worth telling you about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A pomodoro started, with its number.
+    Started(u32),
+    /// A break is due, and whether it's a long one.
+    BreakDue(bool),
+    /// You've taken the (long) break we prompted you for.
+    Rested(bool),
+    /// You worked through the (long) break we prompted you for.
+    Skipped(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct Pomodoro {
+    interval: Interval,
+    /// How many pomodoros you've finished today.
+    done: u32,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Pomodoro {
+            interval: Interval::Waiting,
+            done: 0,
+        }
+    }
+}
+
+impl Pomodoro {
+    /// Moves between intervals, given that you have been idle for `idle`.
+    pub fn update(&mut self, config: &Config, now: Instant, idle: Duration) -> Option<Event> {
+        match self.interval {
+            Interval::Waiting if idle < ACTIVE => {
+                self.interval = Interval::Working(now - idle);
+                Some(Event::Started(self.done + 1))
+            }
+            Interval::Waiting => None,
+            Interval::Working(_) if idle > config.long_break => {
+                // You took a long enough break on your own.
+                self.interval = Interval::Waiting;
+                None
+            }
+            Interval::Working(start) if now.duration_since(start) >= config.work => {
+                self.done += 1;
+                let long = self.done.is_multiple_of(config.long_break_every.max(1));
+                self.interval = Interval::Resting {
+                    since: now,
+                    long,
+                    rested: false,
+                };
+                Some(Event::BreakDue(long))
+            }
+            Interval::Working(_) => None,
+            Interval::Resting {
+                since,
+                long,
+                rested: false,
+            } if idle >= config.break_time(long) => {
+                self.interval = Interval::Resting {
+                    since,
+                    long,
+                    rested: true,
+                };
+                Some(Event::Rested(long))
+            }
+            Interval::Resting {
+                rested: true, long, ..
+            } if idle < config.break_time(long) => {
+                self.interval = Interval::Working(now - idle);
+                Some(Event::Started(self.done + 1))
+            }
+            Interval::Resting {
+                since,
+                long,
+                rested: false,
+            } if now.duration_since(since) > config.work => {
+                self.interval = Interval::Working(now);
+                Some(Event::Skipped(long))
+            }
+            Interval::Resting { .. } => None,
+        }
+    }
+
+    /// What interval we're in, and how many pomodoros you've done.
+    pub fn describe(&self, config: &Config, now: Instant, language: Language) -> String {
+        let interval = match self.interval {
+            Interval::Waiting => Message::PomodoroWaiting,
+            Interval::Working(start) => Message::PomodoroWorking {
+                n: self.done + 1,
+                left: config.work.saturating_sub(now.duration_since(start)),
+            },
+            Interval::Resting { rested: true, .. } => Message::PomodoroBreakTaken,
+            Interval::Resting { since, long, .. } => Message::PomodoroResting {
+                long,
+                left: config
+                    .break_time(long)
+                    .saturating_sub(now.duration_since(since)),
+            },
+        };
+        format!(
+            "{} {}",
+            language.text(interval),
+            language.text(Message::PomodorosDone(self.done))
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn cycle() {
+        let config = Config::default();
+        let mut p = Pomodoro::default();
+        let start = Instant::now();
+        assert_eq!(p.update(&config, start, 5 * MINUTE), None);
+        assert_eq!(
+            p.update(&config, start, Duration::ZERO),
+            Some(Event::Started(1))
+        );
+        assert_eq!(p.update(&config, start + 10 * MINUTE, Duration::ZERO), None);
+        assert_eq!(
+            p.update(&config, start + 25 * MINUTE, Duration::ZERO),
+            Some(Event::BreakDue(false))
+        );
+        // Not idle long enough to count as a break.
+        assert_eq!(p.update(&config, start + 28 * MINUTE, 3 * MINUTE), None);
+        assert_eq!(
+            p.update(&config, start + 30 * MINUTE, 5 * MINUTE),
+            Some(Event::Rested(false))
+        );
+        assert_eq!(p.update(&config, start + 31 * MINUTE, 6 * MINUTE), None);
+        assert_eq!(
+            p.update(&config, start + 32 * MINUTE, Duration::ZERO),
+            Some(Event::Started(2))
+        );
+        assert_eq!(
+            p.describe(&config, start + 42 * MINUTE, Language::English),
+            "Pomodoro 2: 15 minutes to go (1 done today)"
+        );
+    }
+
+    #[test]
+    fn long_break() {
+        let config = Config::default();
+        let mut p = Pomodoro::default();
+        let mut now = Instant::now();
+        for n in 1..=4 {
+            assert_eq!(
+                p.update(&config, now, Duration::ZERO),
+                Some(Event::Started(n))
+            );
+            now += 25 * MINUTE;
+            assert_eq!(
+                p.update(&config, now, Duration::ZERO),
+                Some(Event::BreakDue(n == 4))
+            );
+            now += config.break_time(n == 4);
+            assert_eq!(
+                p.update(&config, now, config.break_time(n == 4)),
+                Some(Event::Rested(n == 4))
+            );
+        }
+    }
+
+    #[test]
+    fn skipped() {
+        let config = Config::default();
+        let mut p = Pomodoro::default();
+        let start = Instant::now();
+        p.update(&config, start, Duration::ZERO);
+        p.update(&config, start + 25 * MINUTE, Duration::ZERO);
+        assert_eq!(
+            p.update(&config, start + 51 * MINUTE, Duration::ZERO),
+            Some(Event::Skipped(false))
+        );
+        assert_eq!(
+            p.describe(&config, start + 51 * MINUTE, Language::English),
+            "Pomodoro 2: 25 minutes to go (1 done today)"
+        );
+        assert_eq!(
+            p.describe(&config, start + 51 * MINUTE, Language::German),
+            "Pomodoro 2: noch 25 Minuten (1 heute erledigt)"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::lang::Language;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How old a status file can be before we decide `breaks` isn't running.
+/// The running `breaks` updates it every ten seconds or so.
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// How things stand, for anyone outside the window who wants to know.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Snapshot {
+    /// Time worked today.
+    #[serde(with = "crate::hours")]
+    pub worked: Duration,
+    /// What's left of the workday.
+    #[serde(with = "crate::hours")]
+    pub remaining: Duration,
+    #[serde(with = "crate::hours")]
+    pub workday: Duration,
+    /// Whether you're away from the computer.
+    pub idle: bool,
+    /// When you started working, or went away, whichever is more recent.
+    #[serde(default)]
+    pub since: chrono::DateTime<chrono::Local>,
+    /// What we're prompting you to do, if anything.
+    pub prompt: Option<String>,
+    /// The break that's due soonest, if we're not prompting for it already.
+    pub next_break: Option<NextBreak>,
+    /// All the breaks, soonest first.
+    #[serde(default)]
+    pub breaks: Vec<NextBreak>,
+    pub language: Language,
+    pub updated: chrono::DateTime<chrono::Local>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NextBreak {
+    pub name: String,
+    #[serde(with = "crate::hours")]
+    pub due_in: Duration,
+}
+
+impl Snapshot {
+    pub fn over_workday(&self) -> bool {
+        self.worked >= self.workday
+    }
+
+    /// Whether this is recent enough to believe, at `now`.
+    pub fn is_current(&self, now: chrono::DateTime<chrono::Local>) -> bool {
+        (now - self.updated)
+            .to_std()
+            .map_or(true, |age| age < STALE_AFTER)
+    }
+
+    /// Writes the status file at `path`, so that it's never seen half
+    /// written.
+    pub fn write(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, serde_json::to_string(self)?)?;
+        std::fs::rename(partial, path)?;
+        Ok(())
+    }
+
+    /// Reads the status file, which is `None` if `breaks` isn't running.
+    pub fn read() -> anyhow::Result<Option<Snapshot>> {
+        let contents = match std::fs::read_to_string(status_path()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let snapshot: Snapshot = serde_json::from_str(&contents)?;
+        Ok(Some(snapshot).filter(|s| s.is_current(chrono::Local::now())))
+    }
+}
+
+pub fn status_path() -> std::path::PathBuf {
+    crate::config_path("breaks-status.json")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let now = chrono::Local::now();
+        let snapshot = Snapshot {
+            worked: Duration::from_secs(5 * 3600 + 12 * 60),
+            remaining: Duration::from_secs(2 * 3600 + 48 * 60),
+            workday: Duration::from_secs(8 * 3600),
+            idle: false,
+            since: now,
+            prompt: None,
+            next_break: Some(NextBreak {
+                name: "stretch".to_string(),
+                due_in: Duration::from_secs(23 * 60),
+            }),
+            breaks: Vec::new(),
+            language: Language::German,
+            updated: now,
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains(r#""worked":"5:12""#));
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+        assert!(snapshot.is_current(now + chrono::Duration::seconds(10)));
+        assert!(!snapshot.is_current(now + chrono::Duration::minutes(5)));
+        assert!(!snapshot.over_workday());
+
+        // As written by a version of breaks from before `since` and `breaks`.
+        let mut old: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let old = old.as_object_mut().unwrap();
+        old.remove("since");
+        old.remove("breaks");
+        let old: Snapshot = serde_json::from_value(old.clone().into()).unwrap();
+        assert_eq!(old.worked, snapshot.worked);
+        assert!(old.breaks.is_empty());
+    }
+}
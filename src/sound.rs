@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Sounds that come with breaks, by name.
+const BUNDLED: &[(&str, &[u8])] = &[
+    ("chime", include_bytes!("../sounds/chime.wav")),
+    ("bell", include_bytes!("../sounds/bell.wav")),
+];
+
+/// Something that can have a sound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    Prompt,
+    Emphasize,
+    EndOfDay,
+    BreakComplete,
+}
+
+const CUES: [Cue; 4] = [
+    Cue::Prompt,
+    Cue::Emphasize,
+    Cue::EndOfDay,
+    Cue::BreakComplete,
+];
+
+/// Which sound to play for each cue, as the name of a bundled sound ("chime"
+/// or "bell") or the path of a WAV or OGG file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// From 0.0 to 1.0.
+    volume: f32,
+    /// Whether to speak after the sound, or leave it at the sound.
+    then_speak: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emphasize: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_of_day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    break_complete: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            volume: 0.5,
+            then_speak: true,
+            prompt: None,
+            emphasize: None,
+            end_of_day: None,
+            break_complete: None,
+        }
+    }
+}
+
+impl Config {
+    fn sound(&self, cue: Cue) -> Option<&str> {
+        match cue {
+            Cue::Prompt => self.prompt.as_deref(),
+            Cue::Emphasize => self.emphasize.as_deref(),
+            Cue::EndOfDay => self.end_of_day.as_deref(),
+            Cue::BreakComplete => self.break_complete.as_deref(),
+        }
+    }
+    /// Whether any cue has a sound.
+    fn any(&self) -> bool {
+        CUES.iter().any(|&cue| self.sound(cue).is_some())
+    }
+    /// Makes sure all the sounds can be found.
+    pub fn check(&self) -> anyhow::Result<()> {
+        for cue in CUES {
+            if let Some(sound) = self.sound(cue) {
+                load(sound).map_err(|e| anyhow::anyhow!("Unable to read sound {sound:?}: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn load(sound: &str) -> std::io::Result<Vec<u8>> {
+    match BUNDLED.iter().find(|(name, _)| *name == sound) {
+        Some((_, bytes)) => Ok(bytes.to_vec()),
+        None => std::fs::read(sound),
+    }
+}
+
+/// Where sounds go.
+#[derive(Clone)]
+enum Output {
+    Speakers,
+    /// Plays nothing, but remembers what it was asked to play.
+    Null(Arc<Mutex<Vec<Cue>>>),
+}
+
+#[derive(Clone)]
+pub struct Player {
+    config: Config,
+    output: Output,
+}
+
+impl Player {
+    pub fn new(config: Config) -> Self {
+        // No need for speakers, or to complain there are none, if there's
+        // nothing to play.
+        if !config.any() {
+            return Player::null(config);
+        }
+        if let Err(e) = rodio::OutputStream::try_default() {
+            eprintln!("Unable to play sounds: {e}");
+            return Player::null(config);
+        }
+        Player {
+            config,
+            output: Output::Speakers,
+        }
+    }
+
+    /// A player for when there is no sound card.
+    pub fn null(config: Config) -> Self {
+        Player {
+            config,
+            output: Output::Null(Default::default()),
+        }
+    }
+
+    /// What a null player was asked to play.
+    #[cfg(test)]
+    fn played(&self) -> Vec<Cue> {
+        match &self.output {
+            Output::Null(played) => played.lock().unwrap().clone(),
+            Output::Speakers => Vec::new(),
+        }
+    }
+
+    /// Plays the sound for `cue`, if it has one, and then calls `speak`
+    /// unless we're to leave it at the sound.
+    pub fn play(&self, cue: Cue, speak: impl FnOnce() + Send + 'static) {
+        let Some(sound) = self.config.sound(cue) else {
+            speak();
+            return;
+        };
+        let then_speak = self.config.then_speak;
+        match &self.output {
+            Output::Null(played) => {
+                played.lock().unwrap().push(cue);
+                if then_speak {
+                    speak();
+                }
+            }
+            Output::Speakers => {
+                let sound = sound.to_string();
+                let volume = self.config.volume;
+                std::thread::spawn(move || {
+                    if let Err(e) = play(&sound, volume) {
+                        eprintln!("Unable to play {sound:?}: {e}");
+                    }
+                    if then_speak {
+                        speak();
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Plays `sound`, waiting until it's done.
+fn play(sound: &str, volume: f32) -> anyhow::Result<()> {
+    let (_stream, handle) = rodio::OutputStream::try_default()?;
+    let sink = rodio::Sink::try_new(&handle)?;
+    sink.set_volume(volume);
+    sink.append(rodio::Decoder::new(std::io::Cursor::new(load(sound)?))?);
+    sink.sleep_until_end();
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn null_player() {
+        let config: Config = toml::from_str("prompt = 'chime'\nthen_speak = false").unwrap();
+        assert!(config.check().is_ok());
+        let player = Player::null(config);
+        let spoken = Arc::new(AtomicUsize::new(0));
+        let speak = || {
+            let spoken = spoken.clone();
+            move || {
+                spoken.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+        player.play(Cue::Prompt, speak());
+        assert_eq!(spoken.load(Ordering::SeqCst), 0);
+        // Without a sound, we just speak.
+        player.play(Cue::Emphasize, speak());
+        assert_eq!(spoken.load(Ordering::SeqCst), 1);
+        assert_eq!(player.played(), vec![Cue::Prompt]);
+
+        let player = Player::null(toml::from_str("end_of_day = 'bell'").unwrap());
+        player.play(Cue::EndOfDay, speak());
+        assert_eq!(spoken.load(Ordering::SeqCst), 2);
+        assert_eq!(player.played(), vec![Cue::EndOfDay]);
+
+        // Without any sounds, we don't look for speakers.
+        let player = Player::new(Config::default());
+        assert!(matches!(player.output, Output::Null(_)));
+
+        let config: Config = toml::from_str("prompt = '/no/such/sound.ogg'").unwrap();
+        assert!(config.check().is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::lang::Language;
+use std::time::Duration;
+
+/// The variables you can use in a prompt, like `{worked}`.
+pub const VARIABLES: &[&str] = &["worked", "remaining", "since_break", "count", "time"];
+
+/// What the variables in a prompt stand for.
+pub struct Values {
+    /// How long you've worked today.
+    pub worked: Duration,
+    /// How much of your workday is left.
+    pub remaining: Duration,
+    /// How long since you came back from a break.
+    pub since_break: Duration,
+    /// How many times this prompt has come up today.
+    pub count: u32,
+    /// The clock time.
+    pub time: chrono::DateTime<chrono::Local>,
+    pub language: Language,
+}
+
+impl Values {
+    /// The values as of now, given how long your `workday` is.
+    pub fn now(
+        worked: Duration,
+        workday: Duration,
+        since_break: Duration,
+        count: u32,
+        language: Language,
+    ) -> Self {
+        Values {
+            worked,
+            remaining: workday.saturating_sub(worked),
+            since_break,
+            count,
+            time: chrono::Local::now(),
+            language,
+        }
+    }
+    fn get(&self, name: &str, spoken: bool) -> Option<String> {
+        let duration = |d: Duration| {
+            if spoken {
+                self.language.spoken_duration(d)
+            } else {
+                self.language.duration(d)
+            }
+        };
+        match name {
+            "worked" => Some(duration(self.worked)),
+            "remaining" => Some(duration(self.remaining)),
+            "since_break" => Some(duration(self.since_break)),
+            "count" => Some(self.count.to_string()),
+            "time" => Some(self.language.clock(self.time)),
+            _ => None,
+        }
+    }
+}
+
+/// A part of a template.
+enum Piece {
+    Text(String),
+    Variable(String),
+    /// A `{` with no `}` after it, along with the rest of the template.
+    Unclosed(String),
+}
+
+/// Splits `template` into literal text and variable names.  `{{` and `}}`
+/// stand for literal braces.
+fn pieces(template: &str) -> Vec<Piece> {
+    let mut out = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                out.push(Piece::Text(std::mem::take(&mut text)));
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                out.push(if closed {
+                    Piece::Variable(name.trim().to_string())
+                } else {
+                    Piece::Unclosed(name)
+                });
+            }
+            c => text.push(c),
+        }
+    }
+    out.push(Piece::Text(text));
+    out
+}
+
+/// Fills in the variables in `template`.  Unknown variables are left as they are.
+pub fn render(template: &str, values: &Values) -> String {
+    fill(template, values, false)
+}
+
+/// Fills in the variables in `template` in a way that suits text to speech.
+pub fn render_spoken(template: &str, values: &Values) -> String {
+    fill(template, values, true)
+}
+
+fn fill(template: &str, values: &Values, spoken: bool) -> String {
+    substitute(template, |name| values.get(name, spoken))
+}
+
+/// Fills in the variables in `template` with whatever `get` says they are.
+/// Unknown variables are left as they are.
+pub fn substitute(template: &str, get: impl Fn(&str) -> Option<String>) -> String {
+    pieces(template)
+        .into_iter()
+        .map(|p| match p {
+            Piece::Text(text) => text,
+            Piece::Variable(name) => get(&name).unwrap_or_else(|| format!("{{{name}}}")),
+            Piece::Unclosed(rest) => format!("{{{rest}"),
+        })
+        .collect()
+}
+
+/// Makes sure `template` only uses variables we know about, and closes its
+/// braces.
+pub fn check(template: &str) -> anyhow::Result<()> {
+    check_variables(template, VARIABLES)
+}
+
+/// Makes sure `template` only uses the `variables` given, and closes its
+/// braces.
+pub fn check_variables(template: &str, variables: &[&str]) -> anyhow::Result<()> {
+    for p in pieces(template) {
+        match p {
+            Piece::Variable(name) if !variables.contains(&name.as_str()) => {
+                anyhow::bail!(
+                    "Unknown variable {{{name}}} in {template:?}, try one of {}",
+                    variables.join(", ")
+                );
+            }
+            Piece::Unclosed(_) => {
+                anyhow::bail!("Missing }} in {template:?}, use {{{{ for a literal {{");
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn templates() {
+        let mut values = Values {
+            worked: Duration::from_secs(5 * 60 * 60 + 12 * 60),
+            remaining: Duration::from_secs(2 * 60 * 60 + 48 * 60),
+            since_break: Duration::from_secs(50 * 60),
+            count: 2,
+            time: chrono::Local
+                .with_ymd_and_hms(2023, 1, 2, 15, 4, 0)
+                .unwrap(),
+            language: Language::English,
+        };
+        assert_eq!(
+            render(
+                "You've worked {worked} today, {remaining} left — time to stretch",
+                &values
+            ),
+            "You've worked 5:12 today, 2:48 left — time to stretch"
+        );
+        assert_eq!(
+            render("{ count } at {time}, {since_break} since a break", &values),
+            "2 at 3:04 PM, 50 minutes since a break"
+        );
+        assert_eq!(
+            render_spoken("You've worked {worked} today", &values),
+            "You've worked five hours and twelve minutes today"
+        );
+        assert_eq!(render("{{worked}} {nope}", &values), "{worked} {nope}");
+        assert_eq!(render("No variables", &values), "No variables");
+        assert_eq!(render("{worked} {worked", &values), "5:12 {worked");
+
+        values.language = Language::German;
+        assert_eq!(
+            render(
+                "{worked} gearbeitet, {since_break} seit der Pause, {time}",
+                &values
+            ),
+            "5:12 gearbeitet, 50 Minuten seit der Pause, 15:04"
+        );
+
+        assert!(check("{worked} and {{literal}}").is_ok());
+        let err = check("{worked} {nope}").unwrap_err().to_string();
+        assert!(err.contains("{nope}"), "{err}");
+        let err = check("{worked} at {time").unwrap_err().to_string();
+        assert!(err.contains("Missing }"), "{err}");
+    }
+}
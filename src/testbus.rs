@@ -0,0 +1,63 @@
+//! A D-Bus daemon of our own, so tests don't depend on (or bother) the
+//! session bus.
+
+use std::io::BufRead;
+use std::process::{Child, Command, Stdio};
+
+const CONFIG: &str = r#"<busconfig>
+  <type>session</type>
+  <listen>unix:tmpdir=/tmp</listen>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#;
+
+pub struct PrivateBus {
+    daemon: Child,
+    address: String,
+    config: std::path::PathBuf,
+}
+
+impl PrivateBus {
+    /// Starts a bus, unless there's no `dbus-daemon` to do it with.
+    pub fn start() -> Option<Self> {
+        let config = std::env::temp_dir().join(format!(
+            "breaks-test-bus-{}-{:?}.conf",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        std::fs::write(&config, CONFIG).ok()?;
+        let mut daemon = Command::new("dbus-daemon")
+            .arg("--nofork")
+            .arg("--print-address")
+            .arg(format!("--config-file={}", config.display()))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        std::io::BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(PrivateBus {
+            daemon,
+            address: address.trim().to_string(),
+            config,
+        })
+    }
+
+    /// Starts connecting to the bus.
+    pub fn builder(&self) -> zbus::blocking::connection::Builder<'static> {
+        zbus::blocking::connection::Builder::address(self.address.as_str()).unwrap()
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        self.daemon.kill().ok();
+        self.daemon.wait().ok();
+        std::fs::remove_file(&self.config).ok();
+    }
+}
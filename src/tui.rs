@@ -0,0 +1,369 @@
+//! Breaks in the terminal, for those who'd rather not have a window.
+
+use crate::action::Action;
+use crate::history::Kind;
+use crate::lang::Message;
+use crate::{hours, notify, State, UPDATE_EVERY};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, LineGauge, Paragraph, Wrap};
+use ratatui::Frame;
+use std::io::Write;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a key before looking for actions from elsewhere.
+const POLL_EVERY: Duration = Duration::from_millis(250);
+
+/// What a key does, like the window's buttons.
+#[derive(Clone, Copy)]
+enum Key {
+    Done,
+    /// Put off the prompt by one of `notify::DELAYS`.
+    Delay(usize),
+    StartRoutine,
+    SkipRoutine,
+    /// Start or stop personal time.
+    Personal,
+    /// Start or stop focusing.
+    Focus,
+    FocusAnyway,
+    /// Answer the question of what you were doing while away.
+    Away(Kind),
+    Quit,
+}
+
+/// The keys, in rows like the window's buttons.
+const KEYS: [&[(char, Key)]; 3] = [
+    &[
+        ('r', Key::StartRoutine),
+        ('x', Key::SkipRoutine),
+        ('d', Key::Done),
+        ('s', Key::Delay(0)),
+        ('S', Key::Delay(1)),
+        ('p', Key::Personal),
+    ],
+    &[('f', Key::Focus), ('F', Key::FocusAnyway), ('q', Key::Quit)],
+    &[
+        ('b', Key::Away(Kind::Break)),
+        ('m', Key::Away(Kind::Meeting)),
+        ('o', Key::Away(Kind::OfflineWork)),
+    ],
+];
+
+impl Key {
+    fn label(self, s: &State) -> String {
+        match self {
+            Key::Done => s.text(Message::Done),
+            Key::Delay(i) => s.text(Message::Delay(notify::DELAYS[i])),
+            Key::StartRoutine => s.text(Message::StartRoutine),
+            Key::SkipRoutine => s.text(Message::SkipRoutine),
+            Key::Personal if s.personal => s.text(Message::BackToWork),
+            Key::Personal => s.text(Message::PersonalTime),
+            Key::Focus if s.focusing() => s.text(Message::EndFocus),
+            Key::Focus => s.text(Message::Focus(s.config.focus_time)),
+            Key::FocusAnyway => s.text(Message::FocusAnyway),
+            Key::Away(Kind::Meeting) => s.text(Message::Meeting),
+            Key::Away(Kind::OfflineWork) => s.text(Message::OfflineWork),
+            Key::Away(_) => s.text(Message::Break),
+            Key::Quit => s.text(Message::Quit),
+        }
+    }
+
+    /// Whether it does anything just now, as when the button is enabled.
+    fn enabled(self, s: &State) -> bool {
+        match self {
+            Key::Done => s.can_be_done(),
+            Key::Delay(_) => s.am_prompting.is_some(),
+            Key::StartRoutine => s.routine.is_some() && !s.doing_routine(),
+            Key::SkipRoutine => s.routine.is_some(),
+            Key::FocusAnyway => s.confirm_focus.is_some(),
+            Key::Away(_) => s.away.is_some(),
+            Key::Personal | Key::Focus | Key::Quit => true,
+        }
+    }
+}
+
+/// Runs breaks in the terminal until you quit.
+pub fn run(mut state: State) -> anyhow::Result<()> {
+    let (tx, actions) = mpsc::channel();
+    crate::start_services(&mut state, move |action| {
+        tx.send(action).ok();
+    });
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut state, &actions);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    state: &mut State,
+    actions: &mpsc::Receiver<Action>,
+) -> anyhow::Result<()> {
+    let mut last_check_in: Option<Instant> = None;
+    let mut next_tick = Instant::now();
+    loop {
+        if Instant::now() >= next_tick {
+            let check_in = last_check_in.is_none_or(|t| t.elapsed() >= UPDATE_EVERY);
+            if check_in {
+                last_check_in = Some(Instant::now());
+            }
+            if state.tick(check_in).insist {
+                // We can't raise the terminal, but we can ring its bell.
+                print!("\x07");
+                std::io::stdout().flush().ok();
+            }
+            next_tick = Instant::now() + state.tick_every();
+        }
+        terminal.draw(|frame| draw(frame, state))?;
+
+        let wait = POLL_EVERY.min(next_tick.saturating_duration_since(Instant::now()));
+        if event::poll(wait)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !press(state, key) {
+                    return Ok(());
+                }
+            }
+        }
+        for action in actions.try_iter() {
+            state.act(action);
+            state.update_notification();
+            state.publish();
+        }
+    }
+}
+
+/// Does what `key` asks, returning false if it's time to quit.
+fn press(state: &mut State, key: KeyEvent) -> bool {
+    let key = match key.code {
+        KeyCode::Esc => Key::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Key::Quit,
+        KeyCode::Char(c) => match KEYS.iter().copied().flatten().find(|(k, _)| *k == c) {
+            Some((_, key)) => *key,
+            None => return true,
+        },
+        _ => return true,
+    };
+    if !key.enabled(state) {
+        return true;
+    }
+    match key {
+        Key::Done => {
+            state.done();
+        }
+        Key::Delay(i) => state.delay(notify::DELAYS[i]),
+        Key::StartRoutine => state.start_routine(),
+        Key::SkipRoutine => state.skip_routine(),
+        Key::Personal => state.set_personal(!state.personal),
+        Key::Focus if state.focusing() => state.end_focus(),
+        Key::Focus => state.focus(state.config.focus_time, false),
+        Key::FocusAnyway => {
+            if let Some(time) = state.confirm_focus {
+                state.focus(time, true);
+            }
+        }
+        Key::Away(kind) => state.spent_away(kind),
+        Key::Quit => return false,
+    }
+    state.update_notification();
+    state.publish();
+    true
+}
+
+/// A progress bar, with how far along it is out of how far it goes.
+struct Bar {
+    label: String,
+    done: Duration,
+    of: Duration,
+    colour: Color,
+}
+
+fn draw(frame: &mut Frame, state: &State) {
+    let worked = state.worktime();
+    let workday = state.config.workday;
+    let mut bars = vec![Bar {
+        label: format!("{} / {}", hours::clock(worked), hours::clock(workday)),
+        done: worked,
+        of: workday,
+        colour: if worked >= workday {
+            Color::Red
+        } else {
+            Color::Green
+        },
+    }];
+    // As in the snapshot, pomodoros take the place of breaks.
+    for b in state
+        .breaks
+        .iter()
+        .filter(|b| !b.micro && state.config.pomodoro.is_none())
+    {
+        let due_in = (b.after + b.last_done).saturating_sub(worked);
+        bars.push(Bar {
+            label: format!("{} ({})", b.label(), hours::clock(due_in)),
+            done: worked.saturating_sub(b.last_done),
+            of: b.after,
+            colour: Color::Blue,
+        });
+    }
+
+    let [text, gauges, keys] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(bars.len() as u16),
+        Constraint::Length(KEYS.len() as u16),
+    ])
+    .areas(frame.area());
+
+    let prompt = state.am_prompting.clone().unwrap_or_default();
+    let away = match state.away {
+        Some((_, away)) => state.text(Message::AwayQuestion(away)),
+        None => String::new(),
+    };
+    let lines = vec![
+        Line::from(prompt).bold().yellow(),
+        Line::from(state.routine_report()),
+        Line::from(state.microbreak_report()),
+        Line::default(),
+        Line::from(state.status_report.clone()),
+        Line::default(),
+        Line::from(state.latest_update.clone()),
+        Line::from(state.pomodoro_report.clone()),
+        Line::default(),
+        Line::from(away).bold(),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title(" breaks ")),
+        text,
+    );
+    draw_bars(frame, gauges, &bars);
+
+    let rows: Vec<Line> = KEYS.iter().map(|row| key_line(state, row)).collect();
+    frame.render_widget(Paragraph::new(rows), keys);
+}
+
+/// A row of keys and what they do, greyed out when they'd do nothing.
+fn key_line(state: &State, row: &[(char, Key)]) -> Line<'static> {
+    let mut hints = Vec::new();
+    for &(c, key) in row {
+        let style = if key.enabled(state) {
+            Style::new()
+        } else {
+            Style::new().dark_gray()
+        };
+        hints.push(Span::styled(format!(" {c} "), style.bold()));
+        hints.push(Span::styled(format!("{}  ", key.label(state)), style));
+    }
+    Line::from(hints)
+}
+
+fn draw_bars(frame: &mut Frame, area: Rect, bars: &[Bar]) {
+    let rows = Layout::vertical(bars.iter().map(|_| Constraint::Length(1))).split(area);
+    for (bar, row) in bars.iter().zip(rows.iter()) {
+        let ratio = bar.done.as_secs_f64() / bar.of.as_secs_f64().max(1.0);
+        frame.render_widget(
+            LineGauge::default()
+                .label(bar.label.clone())
+                .filled_style(Style::new().fg(bar.colour))
+                .ratio(ratio.min(1.0)),
+            *row,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn bars() {
+        let hours = |h: u64| Duration::from_secs(h * 60 * 60);
+        let bars = [
+            Bar {
+                label: "9:00 / 8:00".to_string(),
+                done: hours(9),
+                of: hours(8),
+                colour: Color::Red,
+            },
+            Bar {
+                label: "Stretch".to_string(),
+                done: hours(1),
+                of: hours(4),
+                colour: Color::Blue,
+            },
+        ];
+        let mut terminal = ratatui::Terminal::new(TestBackend::new(20, 2)).unwrap();
+        terminal
+            .draw(|frame| draw_bars(frame, frame.area(), &bars))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| -> String { (0..20).map(|x| buffer[(x, y)].symbol()).collect() };
+        assert_eq!(row(0), "9:00 / 8:00 ────────");
+        // A quarter of the way along what's left after the label.
+        assert_eq!(row(1), "Stretch ────────────");
+        assert_eq!(buffer[(9, 1)].fg, Color::Blue);
+        assert_eq!(buffer[(19, 1)].fg, Color::Reset);
+    }
+
+    #[test]
+    fn keys() {
+        let mut state = State::default();
+        (state.tts, state.player) = (None, crate::sound::Player::null(Default::default()));
+        let temp = |name: &str| {
+            std::env::temp_dir().join(format!("breaks-test-tui-{}-{name}", std::process::id()))
+        };
+        (state.history_path, state.status_path) = (temp("history.jsonl"), temp("status.json"));
+        // Without asking the desktop how long you've been idle.
+        state.config.idle_sources = Vec::new();
+        let press = |state: &mut State, c| {
+            press(state, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+        };
+        let text = |state: &State, row| -> String {
+            key_line(state, KEYS[row])
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect()
+        };
+        assert_eq!(
+            text(&state, 0),
+            " r Start routine   x Skip routine   d Done   s Delay 15 minutes   \
+             S Delay 1 hour   p Personal time  "
+        );
+
+        // Nothing to answer until you've been away.
+        assert!(press(&mut state, 'm'));
+        state.away = Some((Instant::now(), Duration::from_secs(30 * 60)));
+        assert!(press(&mut state, 'm'));
+        assert!(state.away.is_none());
+        assert_eq!(
+            state.status_report,
+            state.text(Message::CountingMeeting(Duration::from_secs(30 * 60)))
+        );
+
+        assert!(press(&mut state, 'p'));
+        assert!(state.personal);
+        assert!(text(&state, 0).ends_with(&format!(" p {}  ", state.text(Message::BackToWork))));
+        assert!(press(&mut state, 'p'));
+        assert!(!state.personal);
+
+        assert!(press(&mut state, 'f'));
+        assert!(state.focusing());
+        assert!(press(&mut state, 'f'));
+        assert!(!state.focusing());
+        // Past the end of the day only when you insist.
+        state.screen_time = state.config.workday;
+        assert!(press(&mut state, 'f'));
+        assert!(!state.focusing());
+        assert!(press(&mut state, 'F'));
+        assert!(state.focusing());
+
+        assert!(!press(&mut state, 'q'));
+        std::fs::remove_file(&state.history_path).ok();
+        std::fs::remove_file(&state.status_path).ok();
+    }
+}
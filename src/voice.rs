@@ -0,0 +1,152 @@
+use crate::lang::Language;
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// How text to speech should sound, and when to keep quiet.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// The name of the voice, as listed at startup.  Without one, we pick a
+    /// voice that speaks your language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// How fast to speak, where 1.0 is normal, 0.0 is as slow as the voice
+    /// goes and 2.0 as fast.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rate: Option<f32>,
+    /// How high to speak, from 0.0 to 2.0 where 1.0 is normal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pitch: Option<f32>,
+    /// How loud to speak, from 0.0 to 2.0 where 1.0 is normal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    volume: Option<f32>,
+    /// When prompts are only shown, not spoken.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl Config {
+    /// Whether it's quiet hours at `now`.
+    pub fn is_quiet(&self, now: NaiveTime) -> bool {
+        match self.quiet_hours {
+            Some(QuietHours { start, end }) if start <= end => start <= now && now < end,
+            // Quiet hours that go past midnight.
+            Some(QuietHours { start, end }) => start <= now || now < end,
+            None => false,
+        }
+    }
+
+    /// Text to speech that sounds the way you asked, speaking `language`.
+    pub fn speaker(&self, language: Language) -> Option<tts::Tts> {
+        let mut tts = tts::Tts::default().ok()?;
+        let voices = tts.voices().unwrap_or_default();
+        if let Some(name) = &self.name {
+            match voices.iter().find(|v| v.name().eq_ignore_ascii_case(name)) {
+                Some(v) => report("voice", tts.set_voice(v).map(|_| ())),
+                None => eprintln!("There is no voice called {name:?}"),
+            }
+        } else {
+            let speaks = |v: &tts::Voice| v.language().to_string().starts_with(language.code());
+            if !tts.voice().ok().flatten().is_some_and(|v| speaks(&v)) {
+                if let Some(v) = voices.iter().find(|v| speaks(v)) {
+                    tts.set_voice(v).ok();
+                }
+            }
+        }
+        if let Some(rate) = self.rate {
+            let rate = scale(rate, tts.min_rate(), tts.normal_rate(), tts.max_rate());
+            report("rate", tts.set_rate(rate).map(|_| ()));
+        }
+        if let Some(pitch) = self.pitch {
+            let pitch = scale(pitch, tts.min_pitch(), tts.normal_pitch(), tts.max_pitch());
+            report("pitch", tts.set_pitch(pitch).map(|_| ()));
+        }
+        if let Some(volume) = self.volume {
+            let volume = scale(
+                volume,
+                tts.min_volume(),
+                tts.normal_volume(),
+                tts.max_volume(),
+            );
+            report("volume", tts.set_volume(volume).map(|_| ()));
+        }
+        Some(tts)
+    }
+}
+
+/// Where `factor` falls between `min` and `max`, with 0 at `min`, 1 at
+/// `normal` and 2 at `max`.  Engines disagree on their ranges, so speech
+/// dispatcher's normal is 0 out of -100 to 100, but others' is 1 or 0.5.
+fn scale(factor: f32, min: f32, normal: f32, max: f32) -> f32 {
+    if factor <= 1.0 {
+        min + factor.max(0.0) * (normal - min)
+    } else {
+        normal + (factor - 1.0).min(1.0) * (max - normal)
+    }
+}
+
+fn report(setting: &str, result: Result<(), tts::Error>) {
+    if let Err(e) = result {
+        eprintln!("Unable to set the {setting} of the voice: {e}");
+    }
+}
+
+/// Prints the voices you can choose from.
+pub fn list(tts: &tts::Tts) {
+    match tts.voices() {
+        Ok(voices) if !voices.is_empty() => {
+            println!("Voices you can use as the name in [voice]:");
+            for v in voices {
+                println!("    {} ({})", v.name(), v.language());
+            }
+        }
+        Ok(_) => (),
+        Err(e) => eprintln!("Unable to list voices: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quiet_hours() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let config: Config =
+            toml::from_str("rate = 1.2\nquiet_hours = { start = '22:00', end = '7:00' }").unwrap();
+        assert_eq!(config.rate, Some(1.2));
+        assert!(config.is_quiet(at(23, 30)));
+        assert!(config.is_quiet(at(6, 59)));
+        assert!(!config.is_quiet(at(7, 0)));
+        assert!(!config.is_quiet(at(12, 0)));
+
+        let config: Config =
+            toml::from_str("quiet_hours = { start = '12:00', end = '13:30' }").unwrap();
+        assert!(config.is_quiet(at(12, 45)));
+        assert!(!config.is_quiet(at(13, 30)));
+
+        assert!(!Config::default().is_quiet(at(3, 0)));
+    }
+
+    #[test]
+    fn scaling() {
+        // Like speech dispatcher.
+        assert_eq!(scale(1.0, -100.0, 0.0, 100.0), 0.0);
+        assert_eq!(scale(1.5, -100.0, 0.0, 100.0), 50.0);
+        assert_eq!(scale(0.8, -100.0, 0.0, 100.0), -20.0);
+        assert_eq!(scale(0.0, -100.0, 0.0, 100.0), -100.0);
+        // Like a voice whose normal is 1 and goes up to 10.
+        assert_eq!(scale(1.0, 0.1, 1.0, 10.0), 1.0);
+        assert_eq!(scale(1.5, 0.1, 1.0, 10.0), 5.5);
+        assert_eq!(scale(0.5, 0.0, 1.0, 10.0), 0.5);
+        // Past the ends stays at the ends.
+        assert_eq!(scale(3.0, 0.0, 0.5, 1.0), 1.0);
+        assert_eq!(scale(-1.0, 0.0, 0.5, 1.0), 0.0);
+    }
+}
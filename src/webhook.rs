@@ -0,0 +1,423 @@
+//! Posting events to web services, like ntfy, Gotify or a chat relay.
+
+use crate::hooks::{Event, Payload};
+use crate::lang::Language;
+use crate::template;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long to wait before trying a failed delivery again, at first.  This
+/// doubles with each failure, up to `MAX_RETRY`.
+const FIRST_RETRY: Duration = Duration::from_secs(5);
+const MAX_RETRY: Duration = Duration::from_secs(5 * 60);
+/// How long to keep trying before giving up on a delivery.
+const GIVE_UP_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// The variables a webhook's headers and body can use.
+const VARIABLES: &[&str] = &[
+    "event",
+    "prompt",
+    "worked",
+    "workday",
+    "remaining",
+    "away",
+    "time",
+];
+
+/// Where to send which events.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Webhook {
+    url: String,
+    /// The events to send, or all of them if this is empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    events: Vec<Event>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    headers: BTreeMap<String, String>,
+    /// What to send, in which strings may use variables like `{prompt}`.  A
+    /// string is sent as plain text, and anything else as JSON.  Without a
+    /// body, we send the event as JSON, just as hooks get it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+}
+
+impl Webhook {
+    pub fn check(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.url.starts_with("http://") || self.url.starts_with("https://"),
+            "The webhook URL {:?} should start with http:// or https://",
+            self.url
+        );
+        for v in self.headers.values() {
+            template::check_variables(v, VARIABLES)?;
+        }
+        if let Some(body) = &self.body {
+            check_json(body)?;
+        }
+        Ok(())
+    }
+
+    /// What to send for `payload`, if anything.
+    fn delivery(&self, payload: &Payload, language: Language) -> Option<Delivery> {
+        if !self.events.is_empty() && !self.events.contains(&payload.event) {
+            return None;
+        }
+        let now = chrono::Local::now();
+        let fill = |text: &str| {
+            template::substitute(text, |name| {
+                let duration = |secs| language.duration(Duration::from_secs(secs));
+                match name {
+                    "event" => Some(payload.event.name().to_string()),
+                    "prompt" => Some(payload.prompt.clone().unwrap_or_default()),
+                    "worked" => Some(duration(payload.worked)),
+                    "workday" => Some(duration(payload.workday)),
+                    "remaining" => Some(duration(payload.workday.saturating_sub(payload.worked))),
+                    "away" => Some(duration(payload.away.unwrap_or_default())),
+                    "time" => Some(language.clock(now)),
+                    _ => None,
+                }
+            })
+        };
+        let (body, json) = match &self.body {
+            Some(serde_json::Value::String(text)) => (fill(text), false),
+            Some(body) => (fill_json(body, &fill).to_string(), true),
+            None => (serde_json::to_string(payload).ok()?, true),
+        };
+        Some(Delivery {
+            url: self.url.clone(),
+            headers: self
+                .headers
+                .iter()
+                .map(|(k, v)| (k.clone(), fill(v)))
+                .collect(),
+            body,
+            json,
+            created: now,
+            attempts: 0,
+        })
+    }
+}
+
+/// Makes sure all the strings in `value` are good templates.
+fn check_json(value: &serde_json::Value) -> anyhow::Result<()> {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => template::check_variables(s, VARIABLES),
+        Value::Array(a) => a.iter().try_for_each(check_json),
+        Value::Object(o) => o.values().try_for_each(check_json),
+        _ => Ok(()),
+    }
+}
+
+/// Fills in the variables in all the strings in `value`.
+fn fill_json(value: &serde_json::Value, fill: &dyn Fn(&str) -> String) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Value::String(fill(s)),
+        Value::Array(a) => Value::Array(a.iter().map(|v| fill_json(v, fill)).collect()),
+        Value::Object(o) => Value::Object(
+            o.iter()
+                .map(|(k, v)| (k.clone(), fill_json(v, fill)))
+                .collect(),
+        ),
+        v => v.clone(),
+    }
+}
+
+/// A request we have yet to make.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct Delivery {
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: String,
+    json: bool,
+    created: chrono::DateTime<chrono::Local>,
+    attempts: u32,
+}
+
+impl Delivery {
+    /// Makes the request, which is `Ok(false)` if it's worth trying again.
+    fn send(&self) -> anyhow::Result<bool> {
+        let content_type = if self.json {
+            "application/json"
+        } else {
+            "text/plain; charset=utf-8"
+        };
+        let mut request = ureq::post(&self.url)
+            .timeout(Duration::from_secs(30))
+            .set("Content-Type", content_type);
+        for (k, v) in &self.headers {
+            request = request.set(k, v);
+        }
+        match request.send_string(&self.body) {
+            Ok(_) => Ok(true),
+            Err(ureq::Error::Status(code, _)) if code == 429 || code >= 500 => {
+                eprintln!("{} answered {code}, will try again", self.url);
+                Ok(false)
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                anyhow::bail!("{} refused the webhook with {code}", self.url)
+            }
+            Err(e) => {
+                eprintln!("Unable to reach {}, will try again: {e}", self.url);
+                Ok(false)
+            }
+        }
+    }
+}
+
+pub fn outbox_path() -> std::path::PathBuf {
+    crate::config_path("breaks-outbox.jsonl")
+}
+
+/// Deliveries waiting to go out, which are kept in a file so that they
+/// aren't lost if we're stopped before the server comes back.
+struct Outbox {
+    path: std::path::PathBuf,
+    waiting: Vec<(Delivery, Instant)>,
+    first_retry: Duration,
+}
+
+impl Outbox {
+    fn load(path: std::path::PathBuf, first_retry: Duration) -> Self {
+        let now = Instant::now();
+        let waiting = std::fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .map(|d| (d, now))
+            .collect();
+        Outbox {
+            path,
+            waiting,
+            first_retry,
+        }
+    }
+
+    fn save(&self) {
+        let write = || -> std::io::Result<()> {
+            if self.waiting.is_empty() {
+                return match std::fs::remove_file(&self.path) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                    _ => Ok(()),
+                };
+            }
+            let mut f = std::fs::File::create(&self.path)?;
+            for (d, _) in &self.waiting {
+                writeln!(f, "{}", serde_json::to_string(d)?)?;
+            }
+            Ok(())
+        };
+        if let Err(e) = write() {
+            eprintln!("Unable to save {:?}: {e}", self.path);
+        }
+    }
+
+    /// Tries the deliveries that are due, returning how long until the next
+    /// one is, if there are any left.
+    fn deliver(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let first_retry = self.first_retry;
+        let before = self.waiting.clone();
+        self.waiting.retain_mut(|(d, next_try)| {
+            if *next_try > now {
+                return true;
+            }
+            match d.send() {
+                Ok(true) => false,
+                Ok(false) => {
+                    d.attempts += 1;
+                    let age = (chrono::Local::now() - d.created)
+                        .to_std()
+                        .unwrap_or_default();
+                    if age > GIVE_UP_AFTER {
+                        eprintln!("Giving up on sending to {}", d.url);
+                        return false;
+                    }
+                    let wait = first_retry * 2u32.saturating_pow(d.attempts - 1);
+                    *next_try = now + wait.min(MAX_RETRY);
+                    true
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    false
+                }
+            }
+        });
+        if self
+            .waiting
+            .iter()
+            .map(|(d, _)| d)
+            .ne(before.iter().map(|(d, _)| d))
+        {
+            self.save();
+        }
+        self.waiting
+            .iter()
+            .map(|(_, next_try)| next_try.saturating_duration_since(Instant::now()))
+            .min()
+    }
+
+    /// Delivers whatever comes in on `deliveries`, until it's closed.
+    fn run(mut self, deliveries: mpsc::Receiver<Delivery>) {
+        loop {
+            let wait = self.deliver();
+            let received = match wait {
+                Some(wait) => deliveries.recv_timeout(wait),
+                None => deliveries
+                    .recv()
+                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(d) => {
+                    self.waiting.push((d, Instant::now()));
+                    self.save();
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+/// Sends events to your webhooks in the background.
+#[derive(Clone)]
+pub struct Sender {
+    webhooks: Vec<Webhook>,
+    language: Language,
+    deliveries: mpsc::Sender<Delivery>,
+}
+
+impl Sender {
+    /// Starts sending to `webhooks`, beginning with anything left over from
+    /// last time.
+    pub fn start(webhooks: Vec<Webhook>, language: Language) -> Self {
+        Sender::with_outbox(webhooks, language, Outbox::load(outbox_path(), FIRST_RETRY))
+    }
+
+    fn with_outbox(webhooks: Vec<Webhook>, language: Language, outbox: Outbox) -> Self {
+        let (deliveries, received) = mpsc::channel();
+        std::thread::spawn(move || outbox.run(received));
+        Sender {
+            webhooks,
+            language,
+            deliveries,
+        }
+    }
+
+    pub fn send(&self, payload: &Payload) {
+        for w in &self.webhooks {
+            if let Some(d) = w.delivery(payload, self.language) {
+                self.deliveries.send(d).ok();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn payload(event: Event) -> Payload {
+        Payload {
+            event,
+            worked: 5 * 3600 + 12 * 60,
+            workday: 8 * 3600,
+            prompt: Some("Stretch \"now\"".to_string()),
+            away: None,
+        }
+    }
+
+    #[test]
+    fn deliveries() {
+        let webhook: Webhook = toml::from_str(
+            r#"
+            url = "https://ntfy.example.com/breaks"
+            events = ["prompt", "end-of-day"]
+            headers = { Title = "Worked {worked}" }
+            body = { message = "{prompt}", tags = ["{event}"], priority = 4 }
+            "#,
+        )
+        .unwrap();
+        assert!(webhook.check().is_ok());
+        let d = webhook
+            .delivery(&payload(Event::Prompt), Language::English)
+            .unwrap();
+        assert_eq!(d.headers["Title"], "Worked 5:12");
+        let body: serde_json::Value = serde_json::from_str(&d.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"message": "Stretch \"now\"", "tags": ["prompt"], "priority": 4})
+        );
+        assert!(d.json);
+        assert!(webhook
+            .delivery(&payload(Event::Resume), Language::English)
+            .is_none());
+
+        let webhook: Webhook =
+            toml::from_str("url = 'http://localhost/'\nbody = '{prompt} ({remaining} left)'")
+                .unwrap();
+        let d = webhook
+            .delivery(&payload(Event::Lock), Language::English)
+            .unwrap();
+        assert_eq!(d.body, "Stretch \"now\" (2:48 left)");
+        assert!(!d.json);
+
+        let webhook: Webhook = toml::from_str("url = 'localhost'").unwrap();
+        assert!(webhook.check().is_err());
+        let webhook: Webhook =
+            toml::from_str("url = 'http://localhost/'\nheaders = { Title = '{count}' }").unwrap();
+        let err = webhook.check().unwrap_err().to_string();
+        assert!(err.contains("{count}"), "{err}");
+        let webhook: Webhook =
+            toml::from_str("url = 'http://localhost/'\nbody = { tags = ['{event', 'ok'] }")
+                .unwrap();
+        assert!(webhook.check().is_err());
+    }
+
+    #[test]
+    fn stub_server() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let webhook: Webhook = toml::from_str(&format!(
+            "url = 'http://127.0.0.1:{port}/hook'\nheaders = {{ Authorization = 'Bearer xyz' }}"
+        ))
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("breaks-outbox-{}.jsonl", std::process::id()));
+        let sender = Sender::with_outbox(
+            vec![webhook],
+            Language::English,
+            Outbox::load(path.clone(), Duration::from_millis(50)),
+        );
+        sender.send(&payload(Event::EndOfDay));
+
+        // The server is down at first, and then comes back.
+        let request = server.recv().unwrap();
+        assert_eq!(request.url(), "/hook");
+        assert!(path.exists());
+        request.respond(tiny_http::Response::empty(503)).unwrap();
+        let mut request = server.recv().unwrap();
+        let auth = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.to_string());
+        assert_eq!(auth.as_deref(), Some("Bearer xyz"));
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        assert_eq!(
+            body,
+            r#"{"event":"end-of-day","worked":18720,"workday":28800,"prompt":"Stretch \"now\""}"#
+        );
+        request.respond(tiny_http::Response::empty(200)).unwrap();
+
+        // Once it's delivered, the outbox is emptied.
+        let start = Instant::now();
+        while path.exists() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!path.exists());
+    }
+}